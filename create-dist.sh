@@ -7,10 +7,11 @@ files=(
     "filled-star.png"
     "index.html"
     "favicon.ico"
-    "puzzles.txt"
     "drag.svg"
     "colors.svg"
     "cross.svg"
+    "sw.js"
+    "data/dictionary.bin"
 )
 
 included_files=(
@@ -30,6 +31,8 @@ for x in "${files[@]}" "${included_files[@]}"; do
     cp -v "$x" "$dn/$bn"
 done
 
+cargo run --release --bin=split-puzzles -- --output dist
+
 pkg_md5=$(cat "${pkg_files[@]/#/pkg\//}" | md5sum - | sed 's/ .*//')
 pkg_dir="dist/pkg-$pkg_md5"
 
@@ -40,11 +43,19 @@ for x in "${pkg_files[@]}"; do
 done
 
 sed -i 's|\./pkg/vaflo\.js|./pkg-'"$pkg_md5"'/vaflo.js|' dist/vaflo.js
+sed -i 's|"pkg/|"pkg-'"$pkg_md5"'/|' dist/sw.js
 
 for x in "${included_files[@]}"; do
     md5=$(md5sum "dist/$x" | sed 's/ .*//')
     new_name=$(echo "$x" | sed 's/\./'"-$md5"'./')
     mv "dist/$x" "dist/$new_name"
     re_filename=$(echo "$x" | sed 's/\./\\./g')
-    sed -i s/\""$re_filename"\"/\""$new_name"\"/g dist/index.html
+    sed -i s/\""$re_filename"\"/\""$new_name"\"/g dist/index.html dist/sw.js
 done
+
+# Files such as the dictionary keep their names, so the cache version
+# is a checksum of every file in the site including sw.js itself. That
+# way it changes whenever anything in the site changes.
+site_md5=$(cat "${files[@]/#/dist\/}" | md5sum - | sed 's/ .*//')
+sed -i 's/^const CACHE_VERSION = .*/const CACHE_VERSION = "'"$site_md5"'";/' \
+    dist/sw.js
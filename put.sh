@@ -2,7 +2,27 @@
 
 set -eu
 
-host=$(sed -nr '/^\s*pub fn share_text\b/,/^    }/ s|\s*https://(.*)".*|\1|p' \
-       src/save_state.rs)
+# lftp bookmark of the FTP server and the directory of the site on it
+bookmark="bdn"
+site_dir="/vaflo.net"
 
-ncftpput bdn "/$host" puzzles.txt
+# Only the puzzles from yesterday until this many days ahead are
+# uploaded so that the later solutions aren’t on the server yet. The
+# script needs to be run again regularly, for example daily from cron,
+# so that the puzzles don’t run out. The service worker fetches up to
+# a week ahead.
+days_ahead=14
+
+tmp_dir=$(mktemp -d)
+trap 'rm -rf "$tmp_dir"' EXIT
+
+cargo run --release --bin=split-puzzles -- \
+      --output "$tmp_dir" \
+      --from "$(date -d yesterday +%F)" \
+      --to "$(date -d "+$days_ahead days" +%F)"
+
+# Mirror the directory so that the files of the puzzles outside of the
+# window or that were moved or removed get deleted from the server
+lftp -c "open $bookmark && \
+         mirror --reverse --delete --verbose \
+                \"$tmp_dir/puzzles\" \"$site_dir/puzzles\""
@@ -25,17 +25,18 @@ mod swap_solver;
 mod grid;
 mod stars;
 mod stem_word;
+mod date;
+mod schedule;
+mod rules;
 
 use std::process::ExitCode;
-use letter_grid::LetterGrid;
 use dictionary::Dictionary;
 use std::sync::{Arc, mpsc, Mutex};
 use std::{fmt, thread};
-use word_grid::WordGrid;
-use grid_solver::GridSolver;
-use std::io::BufRead;
 use grid::Grid;
-use std::collections::{HashMap, VecDeque, hash_map};
+use schedule::ScheduledPuzzle;
+use rules::Problem;
+use std::collections::VecDeque;
 use clap::Parser;
 use std::ffi::OsString;
 
@@ -54,13 +55,7 @@ struct Cli {
 }
 
 enum PuzzleMessageKind {
-    GridParseError(grid::GridParseError),
-    LetterGridParseError(letter_grid::ParseError),
-    SolutionCount(usize),
-    NoSwapSolutionFound,
-    MinimumSwaps(usize),
-    BadWord(String),
-    DuplicateWord(String),
+    Problem(Problem),
     Ok,
 }
 
@@ -74,55 +69,31 @@ struct PuzzleQueue {
 }
 
 struct PuzzleQueueData {
-    next_puzzle_num: usize,
-    jobs: VecDeque<String>,
+    jobs: VecDeque<ScheduledPuzzle>,
 }
 
 impl fmt::Display for PuzzleMessageKind {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             PuzzleMessageKind::Ok => write!(f, "ok"),
-            PuzzleMessageKind::GridParseError(e) => write!(f, "{}", e),
-            PuzzleMessageKind::LetterGridParseError(e) => write!(f, "{}", e),
-            PuzzleMessageKind::SolutionCount(count) => {
-                write!(f, "puzzle has {} solutions", count)
-            },
-            PuzzleMessageKind::NoSwapSolutionFound => {
-                write!(f, "no solution found by swapping letters")
-            },
-            PuzzleMessageKind::MinimumSwaps(swaps) => {
-                write!(f, "minimum number of swaps is {}", swaps)
-            },
-            PuzzleMessageKind::BadWord(word) => {
-                write!(f, "“{}” is not in the dictionary", word.to_uppercase())
-            },
-            PuzzleMessageKind::DuplicateWord(word) => {
-                write!(f, "“{}” appears more than once", word.to_uppercase())
-            },
+            PuzzleMessageKind::Problem(problem) => write!(f, "{}", problem),
         }
     }
 }
 
 impl PuzzleQueue {
-    fn new(jobs: VecDeque<String>, first_puzzle_num: usize) -> PuzzleQueue {
+    fn new(jobs: VecDeque<ScheduledPuzzle>) -> PuzzleQueue {
         PuzzleQueue {
-            data: Mutex::new(PuzzleQueueData {
-                next_puzzle_num: first_puzzle_num,
-                jobs,
-            })
+            data: Mutex::new(PuzzleQueueData { jobs })
         }
     }
 
-    fn next(&self) -> Option<(usize, String)> {
+    fn next(&self) -> Option<(usize, Grid)> {
         let mut data = self.data.lock().unwrap();
 
         data.jobs
             .pop_front()
-            .map(|job| {
-                let puzzle_num = data.next_puzzle_num;
-                data.next_puzzle_num += 1;
-                (puzzle_num, job)
-            })
+            .map(|job| (job.puzzle_number(), job.grid))
     }
 }
 
@@ -150,30 +121,26 @@ fn load_dictionary(filename: Option<OsString>) -> Result<Arc<Dictionary>, ()> {
     }
 }
 
-fn load_puzzles(filename: Option<OsString>) -> Result<VecDeque<String>, ()> {
+fn load_puzzles(
+    filename: Option<OsString>,
+) -> Result<VecDeque<ScheduledPuzzle>, ()> {
     let filename = filename.unwrap_or("puzzles.txt".into());
 
-    let mut puzzles = VecDeque::new();
-
-    let f = match std::fs::File::open(&filename) {
-        Ok(f) => f,
+    let data = match std::fs::read_to_string(&filename) {
+        Ok(data) => data,
         Err(e) => {
             eprintln!("{}: {}", filename.to_string_lossy(), e);
             return Err(());
         },
     };
 
-    for line in std::io::BufReader::new(f).lines() {
-        let line = match line {
-            Ok(line) => line,
-            Err(e) => {
-                eprintln!("{}: {}", filename.to_string_lossy(), e);
-                return Err(());
-            },
-        };
-
-        puzzles.push_back(line);
-    }
+    let puzzles = match schedule::parse(&data) {
+        Ok(puzzles) => VecDeque::from(puzzles),
+        Err(e) => {
+            eprintln!("{}: {}", filename.to_string_lossy(), e);
+            return Err(());
+        },
+    };
 
     if puzzles.is_empty() {
         eprintln!("{}: empty file", filename.to_string_lossy());
@@ -183,131 +150,33 @@ fn load_puzzles(filename: Option<OsString>) -> Result<VecDeque<String>, ()> {
     Ok(puzzles)
 }
 
-fn count_solutions(grid: &LetterGrid, dictionary: &Dictionary) -> usize {
-    let mut solver = GridSolver::new(
-        WordGrid::new(grid),
-        dictionary,
-    );
-
-    let mut count = 0;
-
-    while solver.next().is_some() {
-        count += 1;
-    }
-
-    count
-}
-
-fn check_words(
-    dictionary: &Dictionary,
-    puzzle_num: usize,
-    grid: &Grid,
-    tx: &mpsc::Sender<PuzzleMessage>,
-) -> Result<(), mpsc::SendError<PuzzleMessage>> {
-    let mut words = HashMap::new();
-
-    for positions in grid::WordPositions::new() {
-        let word_chars = positions.map(|pos| grid.solution.letters[pos]);
-        let word = || { word_chars.clone().collect::<String>() };
-
-        let mut stem = word();
-        let stem_len = stem_word::stem(&stem).len();
-        stem.truncate(stem_len);
-
-        match words.entry(stem) {
-            hash_map::Entry::Occupied(entry) => {
-                let counter = entry.into_mut();
-
-                if *counter == 1 {
-                    tx.send(PuzzleMessage {
-                        puzzle_num,
-                        kind: PuzzleMessageKind::DuplicateWord(word()),
-                    })?;
-                }
-
-                *counter += 1;
-            },
-            hash_map::Entry::Vacant(entry) => {
-                if !dictionary.contains(word_chars.clone()) {
-                    tx.send(PuzzleMessage {
-                        puzzle_num,
-                        kind: PuzzleMessageKind::BadWord(word()),
-                    })?;
-                }
-
-                entry.insert(1);
-            },
-        }
-    }
-
-    Ok(())
-}
-
 fn check_puzzles(
     dictionary: &Dictionary,
     puzzles: &PuzzleQueue,
     tx: mpsc::Sender<PuzzleMessage>,
 ) -> Result<(), mpsc::SendError<PuzzleMessage>> {
-    while let Some((puzzle_num, puzzle_string)) = puzzles.next() {
+    while let Some((puzzle_num, grid)) = puzzles.next() {
         let mut ok = true;
 
-        let grid = match puzzle_string.parse::<Grid>() {
-            Ok(grid) => grid,
-            Err(e) => {
-                tx.send(PuzzleMessage {
-                    puzzle_num,
-                    kind: PuzzleMessageKind::GridParseError(e),
-                })?;
-                continue;
-            },
+        let send_problem = |problem| {
+            tx.send(PuzzleMessage {
+                puzzle_num,
+                kind: PuzzleMessageKind::Problem(problem),
+            })
         };
 
-        check_words(dictionary, puzzle_num, &grid, &tx)?;
-
-        match LetterGrid::from_grid(&grid) {
-            Ok(letter_grid) => {
-                let solution_count = count_solutions(&letter_grid, dictionary);
-
-                if solution_count != 1 {
-                    tx.send(PuzzleMessage {
-                        puzzle_num,
-                        kind: PuzzleMessageKind::SolutionCount(solution_count),
-                    })?;
-
-                    ok = false;
-                }
-            },
-            Err(e) => {
-                tx.send(PuzzleMessage {
-                    puzzle_num,
-                    kind: PuzzleMessageKind::LetterGridParseError(e),
-                })?;
-
-                ok = false;
-            },
+        for problem in rules::check_words(dictionary, &grid) {
+            send_problem(problem)?;
         }
 
-        match minimum_swaps(&grid) {
-            Some(swaps) => {
-                if swaps != stars::MAXIMUM_SWAPS as usize
-                    - stars::MAXIMUM_STARS as usize
-                {
-                    tx.send(PuzzleMessage {
-                        puzzle_num,
-                        kind: PuzzleMessageKind::MinimumSwaps(swaps),
-                    })?;
-
-                    ok = false;
-                }
-            },
-            None => {
-                tx.send(PuzzleMessage {
-                    puzzle_num,
-                    kind: PuzzleMessageKind::NoSwapSolutionFound,
-                })?;
+        if let Some(problem) = rules::check_solutions(dictionary, &grid) {
+            send_problem(problem)?;
+            ok = false;
+        }
 
-                ok = false;
-            },
+        if let Some(problem) = rules::check_minimum_swaps(minimum_swaps(&grid)) {
+            send_problem(problem)?;
+            ok = false;
         }
 
         if ok {
@@ -334,18 +203,15 @@ fn main() -> ExitCode {
         return ExitCode::FAILURE;
     };
 
-    let mut first_puzzle_num = 0;
-
     if let Some(last) = cli.last {
         if let Some(to_remove) = puzzles.len().checked_sub(last) {
             puzzles.drain(0..to_remove);
-            first_puzzle_num = to_remove;
         }
     }
 
     let n_puzzles = puzzles.len();
 
-    let puzzles = Arc::new(PuzzleQueue::new(puzzles, first_puzzle_num));
+    let puzzles = Arc::new(PuzzleQueue::new(puzzles));
 
     let (tx, rx) = mpsc::channel();
     let n_threads = Into::<usize>::into(
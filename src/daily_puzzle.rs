@@ -0,0 +1,182 @@
+// Vaflo – A word game in Esperanto
+// Copyright (C) 2025  Neil Roberts
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+// The website has a separate file for each day’s puzzle so that the
+// game only needs to download the puzzle that it is going to show.
+// The contents are scrambled with a key derived from the date. That
+// isn’t real encryption because anyone can work out the key from the
+// source code, but it means the solution can’t be read just by
+// looking at the response in the browser’s developer tools. The
+// first line of the file is the grid and the rest of the file has the
+// definitions of its words in the format of the definitions file.
+
+use std::fmt;
+use super::grid::{self, Grid};
+use super::date::Date;
+use super::definitions::{self, Definitions};
+
+// Directory on the website containing the puzzle files
+pub const DIRECTORY: &str = "puzzles";
+
+#[derive(Debug)]
+pub enum DecodeError {
+    InvalidUtf8,
+    InvalidGrid(grid::GridParseError),
+    InvalidDefinitions(definitions::ParseError),
+}
+
+pub struct DailyPuzzle {
+    pub grid: Grid,
+    pub definitions: Definitions,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::InvalidUtf8 => write!(f, "invalid UTF-8"),
+            DecodeError::InvalidGrid(e) => write!(f, "{}", e),
+            DecodeError::InvalidDefinitions(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+// The path of the puzzle file for the date relative to the root of
+// the website
+pub fn path(date: Date) -> String {
+    format!("{}/{}.bin", DIRECTORY, date)
+}
+
+// Xorshift generator used to make the key stream
+struct KeyStream {
+    state: u32,
+}
+
+impl KeyStream {
+    fn new(date: Date) -> KeyStream {
+        let state = (date.to_days() as u32)
+            .wrapping_mul(0x9e3779b9)
+            ^ 0x5641464c;
+
+        // Xorshift gets stuck if the state is zero
+        KeyStream { state: if state == 0 { 1 } else { state } }
+    }
+}
+
+impl Iterator for KeyStream {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+
+        Some((self.state >> 24) as u8)
+    }
+}
+
+fn scramble(date: Date, data: &[u8]) -> Vec<u8> {
+    data.iter().zip(KeyStream::new(date)).map(|(&a, b)| a ^ b).collect()
+}
+
+// Encodes the grid along with the definitions of any of its words
+// that are in `definitions`
+pub fn encode(date: Date, grid: &Grid, definitions: &Definitions) -> Vec<u8> {
+    let mut s = grid.to_string();
+
+    let definitions = definitions.for_grid(grid);
+
+    if !definitions.is_empty() {
+        s.push('\n');
+        s.push_str(&definitions.to_string());
+    }
+
+    scramble(date, s.as_bytes())
+}
+
+pub fn decode(date: Date, data: &[u8]) -> Result<DailyPuzzle, DecodeError> {
+    let data = scramble(date, data);
+
+    let Ok(s) = std::str::from_utf8(&data)
+    else {
+        return Err(DecodeError::InvalidUtf8);
+    };
+
+    let (grid, definitions) = s.split_once('\n').unwrap_or((s, ""));
+
+    let grid = grid.parse::<Grid>().map_err(DecodeError::InvalidGrid)?;
+    let definitions = definitions::parse(definitions)
+        .map_err(DecodeError::InvalidDefinitions)?;
+
+    Ok(DailyPuzzle { grid, definitions })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const PUZZLE: &str = "MORSAUUKROLASDOOURSOJ\
+                          arcdnhfjvlmewpxbukoty";
+
+    #[test]
+    fn round_trip() {
+        let grid = PUZZLE.parse::<Grid>().unwrap();
+        let date = Date::new(2024, 1, 1);
+
+        let data = encode(date, &grid, &Definitions::new());
+
+        assert_ne!(&data, PUZZLE.as_bytes());
+
+        let puzzle = decode(date, &data).unwrap();
+        assert_eq!(puzzle.grid.to_string(), PUZZLE);
+        assert!(puzzle.definitions.is_empty());
+
+        // Each day has a different key
+        assert_ne!(encode(date.add_days(1), &grid, &Definitions::new()), data);
+    }
+
+    #[test]
+    fn with_definitions() {
+        let grid = PUZZLE.parse::<Grid>().unwrap();
+        let date = Date::new(2024, 1, 1);
+        let definitions = definitions::parse(
+            "MORSA morsi\n\
+             KATO besto\n"
+        ).unwrap();
+
+        let data = encode(date, &grid, &definitions);
+        let puzzle = decode(date, &data).unwrap();
+
+        assert_eq!(puzzle.grid.to_string(), PUZZLE);
+        // Only the definitions for the words in the puzzle are included
+        assert_eq!(&puzzle.definitions.to_string(), "MORSA morsi\n");
+    }
+
+    #[test]
+    fn wrong_date() {
+        let grid = PUZZLE.parse::<Grid>().unwrap();
+        let data = encode(Date::new(2024, 1, 1), &grid, &Definitions::new());
+
+        assert!(decode(Date::new(2024, 1, 2), &data).is_err());
+    }
+
+    #[test]
+    fn path() {
+        assert_eq!(
+            &super::path(Date::new(2023, 8, 7)),
+            "puzzles/2023-08-07.bin",
+        );
+    }
+}
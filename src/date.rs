@@ -0,0 +1,248 @@
+// Vaflo – A word game in Esperanto
+// Copyright (C) 2025  Neil Roberts
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::fmt;
+use std::str::FromStr;
+
+// Date of the first puzzle. Each following puzzle is one day later.
+pub const FIRST_PUZZLE_YEAR: i32 = 2023;
+pub const FIRST_PUZZLE_MONTH: u32 = 8;
+pub const FIRST_PUZZLE_DAY: u32 = 7;
+
+pub const N_WEEKDAYS: usize = 7;
+
+#[derive(Debug)]
+pub struct ParseError;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    pub year: i32,
+    // 1-12
+    pub month: u32,
+    // 1-31
+    pub day: u32,
+}
+
+impl Date {
+    pub fn new(year: i32, month: u32, day: u32) -> Date {
+        Date { year, month, day }
+    }
+
+    // Number of days since 1970-01-01. This uses the algorithm from
+    // http://howardhinnant.github.io/date_algorithms.html
+    pub fn to_days(self) -> i64 {
+        let year = self.year as i64 - (self.month <= 2) as i64;
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let month = self.month as i64;
+        let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2)
+            / 5
+            + self.day as i64
+            - 1;
+        let day_of_era = year_of_era * 365
+            + year_of_era / 4
+            - year_of_era / 100
+            + day_of_year;
+
+        era * 146097 + day_of_era - 719468
+    }
+
+    pub fn from_days(days: i64) -> Date {
+        let days = days + 719468;
+        let era = days.div_euclid(146097);
+        let day_of_era = days - era * 146097;
+        let year_of_era = (day_of_era
+                           - day_of_era / 1460
+                           + day_of_era / 36524
+                           - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era
+            - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = year_of_era + era * 400 + (month <= 2) as i64;
+
+        Date { year: year as i32, month, day }
+    }
+
+    pub fn first_puzzle() -> Date {
+        Date::new(FIRST_PUZZLE_YEAR, FIRST_PUZZLE_MONTH, FIRST_PUZZLE_DAY)
+    }
+
+    pub fn for_puzzle(puzzle_num: usize) -> Date {
+        Date::from_days(Date::first_puzzle().to_days() + puzzle_num as i64)
+    }
+
+    // The number of the puzzle that should be played on this date,
+    // or None if the date is before the first puzzle
+    pub fn puzzle_number(self) -> Option<usize> {
+        usize::try_from(self.to_days() - Date::first_puzzle().to_days()).ok()
+    }
+
+    pub fn add_days(self, days: i64) -> Date {
+        Date::from_days(self.to_days() + days)
+    }
+
+    // The day of the week where 0 is Monday
+    pub fn weekday(self) -> usize {
+        // 1970-01-01 was a Thursday
+        (self.to_days() + 3).rem_euclid(N_WEEKDAYS as i64) as usize
+    }
+}
+
+// Parses a date in the form YYYY-MM-DD
+impl FromStr for Date {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Date, ParseError> {
+        let mut parts = s.split('-');
+
+        let (Some(year), Some(month), Some(day), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(ParseError);
+        };
+
+        let is_number = |part: &str, len| {
+            part.len() == len && part.bytes().all(|b| b.is_ascii_digit())
+        };
+
+        if !is_number(year, 4) || !is_number(month, 2) || !is_number(day, 2) {
+            return Err(ParseError);
+        }
+
+        let (Ok(year), Ok(month), Ok(day)) =
+            (year.parse::<u16>(), month.parse::<u32>(), day.parse::<u32>())
+        else {
+            return Err(ParseError);
+        };
+
+        let date = Date::new(year as i32, month, day);
+
+        // Make sure the date actually exists by checking that it
+        // survives a round trip through the number of days
+        if Date::from_days(date.to_days()) == date {
+            Ok(date)
+        } else {
+            Err(ParseError)
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid date")
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn days() {
+        assert_eq!(Date::new(1970, 1, 1).to_days(), 0);
+        assert_eq!(Date::new(1970, 1, 2).to_days(), 1);
+        assert_eq!(Date::new(1969, 12, 31).to_days(), -1);
+        assert_eq!(Date::new(2000, 3, 1).to_days(), 11017);
+        assert_eq!(Date::new(2023, 8, 7).to_days(), 19576);
+
+        for days in -1000..30000 {
+            let date = Date::from_days(days);
+            assert_eq!(date.to_days(), days);
+            assert!((1..=12).contains(&date.month));
+            assert!((1..=31).contains(&date.day));
+        }
+
+        assert_eq!(Date::from_days(11016), Date::new(2000, 2, 29));
+    }
+
+    #[test]
+    fn puzzles() {
+        assert_eq!(Date::for_puzzle(0), Date::new(2023, 8, 7));
+        assert_eq!(Date::for_puzzle(25), Date::new(2023, 9, 1));
+        assert_eq!(Date::for_puzzle(147), Date::new(2024, 1, 1));
+        assert_eq!(&Date::for_puzzle(0).to_string(), "2023-08-07");
+    }
+
+    #[test]
+    fn puzzle_number() {
+        assert_eq!(Date::new(2023, 8, 7).puzzle_number(), Some(0));
+        assert_eq!(Date::new(2024, 1, 1).puzzle_number(), Some(147));
+        assert_eq!(Date::new(2023, 8, 6).puzzle_number(), None);
+
+        for puzzle_num in 0..1000 {
+            assert_eq!(
+                Date::for_puzzle(puzzle_num).puzzle_number(),
+                Some(puzzle_num),
+            );
+        }
+
+        assert_eq!(
+            Date::new(2024, 2, 28).add_days(2),
+            Date::new(2024, 3, 1),
+        );
+        assert_eq!(
+            Date::new(2025, 1, 1).add_days(-1),
+            Date::new(2024, 12, 31),
+        );
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            "2023-08-07".parse::<Date>().unwrap(),
+            Date::new(2023, 8, 7),
+        );
+        assert_eq!(
+            "2024-02-29".parse::<Date>().unwrap(),
+            Date::new(2024, 2, 29),
+        );
+
+        for bad in [
+            "",
+            "2023-08",
+            "2023-08-07-01",
+            "2023-8-07",
+            "23-08-07",
+            "2023-13-01",
+            "2023-00-01",
+            "2023-02-29",
+            "2023-04-31",
+            "2023-08-00",
+            "2023-08-+7",
+            "abcd-ef-gh",
+        ] {
+            assert!(bad.parse::<Date>().is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn weekday() {
+        // The first puzzle was on a Monday
+        assert_eq!(Date::for_puzzle(0).weekday(), 0);
+        assert_eq!(Date::for_puzzle(6).weekday(), 6);
+        assert_eq!(Date::for_puzzle(7).weekday(), 0);
+        assert_eq!(Date::new(1970, 1, 1).weekday(), 3);
+        assert_eq!(Date::new(2025, 12, 25).weekday(), 3);
+    }
+}
@@ -0,0 +1,200 @@
+// Vaflo – A word game in Esperanto
+// Copyright (C) 2025  Neil Roberts
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+// The definitions file has a line for each word in the form ‘WORD
+// definition’ where the word is written in capitals the same way as
+// it appears in the puzzles. The definition is either a short text or
+// a link to a page about the word. Empty lines and lines starting
+// with ‘#’ are ignored.
+
+use std::fmt;
+use std::collections::BTreeMap;
+use super::grid::{self, Grid};
+
+#[derive(Debug)]
+pub enum ParseError {
+    MissingDefinition(usize),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::MissingDefinition(line_num) => {
+                write!(f, "line {}: missing definition", line_num)
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Definitions {
+    words: BTreeMap<String, String>,
+}
+
+// Returns true if the definition should be shown as a link instead of
+// as text
+pub fn is_link(definition: &str) -> bool {
+    definition.starts_with("https://") || definition.starts_with("http://")
+}
+
+// The words of the grid’s solution in the same order as
+// grid::WordPositions
+pub fn grid_words(grid: &Grid) -> impl Iterator<Item = String> + '_ {
+    grid::WordPositions::new().map(|positions| {
+        positions.map(|pos| grid.solution.letters[pos]).collect()
+    })
+}
+
+impl Definitions {
+    pub fn new() -> Definitions {
+        Definitions::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    pub fn get(&self, word: &str) -> Option<&str> {
+        self.words.get(word).map(String::as_str)
+    }
+
+    // Sets the definition for the word. An empty definition removes
+    // it instead.
+    pub fn set(&mut self, word: &str, definition: &str) {
+        let definition = definition.trim();
+
+        if definition.is_empty() {
+            self.words.remove(word);
+        } else {
+            self.words.insert(word.to_string(), definition.to_string());
+        }
+    }
+
+    // Makes a copy with only the definitions for the words in the
+    // grid
+    pub fn for_grid(&self, grid: &Grid) -> Definitions {
+        let mut definitions = Definitions::new();
+
+        for word in grid_words(grid) {
+            if let Some(definition) = self.words.get(&word) {
+                definitions.words.insert(word, definition.clone());
+            }
+        }
+
+        definitions
+    }
+}
+
+pub fn parse(s: &str) -> Result<Definitions, ParseError> {
+    let mut definitions = Definitions::new();
+
+    for (line_num, line) in s.lines().enumerate() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let Some((word, definition)) = line.split_once(char::is_whitespace)
+        else {
+            return Err(ParseError::MissingDefinition(line_num + 1));
+        };
+
+        definitions.set(word, definition);
+    }
+
+    Ok(definitions)
+}
+
+impl fmt::Display for Definitions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (word, definition) in self.words.iter() {
+            writeln!(f, "{} {}", word, definition)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_and_write() {
+        let definitions = parse(
+            "# comment\n\
+             ŜOVAS  movi ion per premado\n\
+             \n\
+             KATO https://vortaro.net/#kato\n"
+        ).unwrap();
+
+        assert_eq!(definitions.get("ŜOVAS"), Some("movi ion per premado"));
+        assert_eq!(definitions.get("KATO"), Some("https://vortaro.net/#kato"));
+        assert_eq!(definitions.get("HUNDO"), None);
+
+        assert_eq!(
+            &definitions.to_string(),
+            "KATO https://vortaro.net/#kato\n\
+             ŜOVAS movi ion per premado\n",
+        );
+
+        assert!(matches!(
+            parse("KATO besto\nHUNDO"),
+            Err(ParseError::MissingDefinition(2)),
+        ));
+        assert!(parse("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn set() {
+        let mut definitions = Definitions::new();
+
+        definitions.set("KATO", " besto ");
+        assert_eq!(definitions.get("KATO"), Some("besto"));
+
+        definitions.set("KATO", "");
+        assert!(definitions.is_empty());
+    }
+
+    #[test]
+    fn for_grid() {
+        let grid = "MORSAUUKROLASDOOURSOJ\
+                    arcdnhfjvlmewpxbukoty"
+            .parse::<Grid>()
+            .unwrap();
+
+        assert_eq!(
+            grid_words(&grid).collect::<Vec<_>>(),
+            vec!["MORSA", "MURDU", "ROLAS", "RULOS", "URSOJ", "AKSOJ"],
+        );
+
+        let definitions = parse(
+            "MORSA morsi\n\
+             KATO besto\n\
+             AKSOJ aksoj\n"
+        ).unwrap().for_grid(&grid);
+
+        assert_eq!(
+            &definitions.to_string(),
+            "AKSOJ aksoj\n\
+             MORSA morsi\n",
+        );
+
+        assert!(is_link("https://vortaro.net/#kato"));
+        assert!(!is_link("besto"));
+    }
+}
@@ -191,6 +191,56 @@ impl<'a> WordIterator<'a> {
     }
 }
 
+// Small dictionaries shared by the tests of the modules that need one.
+// Not every binary that includes this module has tests that use both.
+#[cfg(test)]
+#[allow(dead_code)]
+pub mod test_data {
+    use super::Dictionary;
+
+    // Dictionary that contains the words “abcde”, “fghij”, “klmno”,
+    // “pqrst”, “uvwxy”, “afkpu”, “bglqv”, “chmrw”, “dinsx” and
+    // “ejoty”. These are the words of a grid whose solution is the
+    // alphabet in order without the letters in the gaps.
+    pub fn grid_words() -> Dictionary {
+        static DICTIONARY_BYTES: [u8; 150] = [
+            0x00, 0x01, 0x2a, 0x01, 0x16, b'a', 0x01, 0x1f, b'b',
+            0x01, 0x1f, b'c', 0x01, 0x1f, b'd', 0x01, 0x1f, b'e',
+            0x01, 0x10, b'f', 0x01, 0x1c, b'k', 0x04, 0x1c, b'p',
+            0x04, 0x1f, b'b', 0x00, 0x19, b'u', 0x00, 0x1f, b'f',
+            0x00, 0x19, b'g', 0x00, 0x1c, b'g', 0x00, 0x1f, b'h',
+            0x00, 0x1f, b'i', 0x00, 0x1f, b'j', 0x00, 0x13, b'l',
+            0x00, 0x1c, b'q', 0x00, 0x1c, b'v', 0x00, 0x1c, b'c',
+            0x00, 0x1c, b'h', 0x00, 0x1f, b'k', 0x00, 0x1f, b'l',
+            0x00, 0x16, b'm', 0x00, 0x1c, b'm', 0x00, 0x1f, b'n',
+            0x00, 0x1f, b'o', 0x00, 0x16, b'r', 0x00, 0x1c, b'w',
+            0x00, 0x1c, b'd', 0x00, 0x1c, b'i', 0x00, 0x1c, b'n',
+            0x00, 0x1f, b'p', 0x00, 0x1f, b'q', 0x00, 0x1f, b'r',
+            0x00, 0x13, b's', 0x00, 0x1c, b's', 0x00, 0x1c, b't',
+            0x00, 0x19, b'x', 0x00, 0x19, b'e', 0x00, 0x16, b'j',
+            0x00, 0x13, b'o', 0x00, 0x10, b't', 0x00, 0x0d, b'u',
+            0x00, 0x0a, b'v', 0x00, 0x07, b'w', 0x00, 0x04, b'x',
+            0x00, 0x01, b'y', 0x00, 0x00, 0x00,
+        ];
+
+        Dictionary::new(Box::new(DICTIONARY_BYTES.clone()))
+    }
+
+    // Dictionary with the words: etoso, haŭto, ninĵo, ratoj
+    pub fn search_words() -> Dictionary {
+        static DICTIONARY_BYTES: [u8; 62] = [
+            0x00, 0x01, 0x2a, 0x01, 0x13, 0x65, 0x01, 0x0a, 0x68, 0x01, 0x0a, 0x6e,
+            0x00, 0x01, 0x72, 0x00, 0x10, 0x61, 0x00, 0x10, 0x61, 0x00, 0x04, 0x69,
+            0x00, 0x04, 0x74, 0x00, 0x14, 0x6e, 0x00, 0x0b, 0x6f, 0x00, 0x05, 0x74,
+            0x00, 0x08, 0xc5, 0xad, 0x00, 0x0b, 0x6f, 0x00, 0x0b, 0x73, 0x00, 0x08,
+            0x74, 0x00, 0x05, 0xc4, 0xb5, 0x00, 0x04, 0x6a, 0x00, 0x01, 0x6f, 0x00,
+            0x00, 0x00,
+        ];
+
+        Dictionary::new(Box::new(DICTIONARY_BYTES.clone()))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
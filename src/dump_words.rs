@@ -15,56 +15,74 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 mod grid;
+mod date;
+mod schedule;
 
 use std::process::ExitCode;
-use grid::Grid;
-use std::collections::HashMap;
-use std::io::BufRead;
-
-fn load_puzzles() -> Result<Vec<Grid>, ()> {
-    let filename = "puzzles.txt";
-    let mut puzzles = Vec::new();
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::OsString;
+use schedule::ScheduledPuzzle;
+use clap::Parser;
+
+#[derive(Parser)]
+#[command(name = "dump-words")]
+struct Cli {
+    #[arg(short, long, value_name = "FILE")]
+    puzzles: Option<OsString>,
+    #[arg(long, value_name = "FILE",
+          help = "Other puzzles file to include in the counts. Can be \
+                  given more than once.")]
+    history: Vec<OsString>,
+}
 
-    let f = match std::fs::File::open(filename) {
-        Ok(f) => f,
+fn load_puzzles(filename: &OsString) -> Result<Vec<ScheduledPuzzle>, ()> {
+    let data = match std::fs::read_to_string(filename) {
+        Ok(data) => data,
         Err(e) => {
-            eprintln!("{}: {}", filename, e);
+            eprintln!("{}: {}", filename.to_string_lossy(), e);
             return Err(());
         },
     };
 
-    for (line_num, line) in std::io::BufReader::new(f).lines().enumerate() {
-        let line = match line {
-            Ok(line) => line,
-            Err(e) => {
-                eprintln!("{}: {}", filename, e);
-                return Err(());
-            },
-        };
-
-        let grid = match line.parse::<Grid>() {
-            Ok(grid) => grid,
-            Err(e) => {
-                eprintln!("{}:{} {}", filename, line_num + 1, e);
-                return Err(());
-            },
-        };
-
-        puzzles.push(grid);
-    }
+    let puzzles = match schedule::parse(&data) {
+        Ok(puzzles) => puzzles,
+        Err(e) => {
+            eprintln!("{}: {}", filename.to_string_lossy(), e);
+            return Err(());
+        },
+    };
 
     if puzzles.is_empty() {
-        eprintln!("{}: empty file", filename);
+        eprintln!("{}: empty file", filename.to_string_lossy());
         return Err(());
     }
 
     Ok(puzzles)
 }
 
-fn count_words(puzzles: &[Grid]) -> HashMap<String, Vec<usize>> {
+// Loads the history files followed by the puzzles file. If more than
+// one file has a puzzle for the same day then the last one is used.
+fn load_all_puzzles(cli: &Cli) -> Result<Vec<ScheduledPuzzle>, ()> {
+    let puzzles_filename = cli.puzzles.clone()
+        .unwrap_or("puzzles.txt".into());
+    let mut puzzles = BTreeMap::new();
+
+    for filename in cli.history.iter().chain(Some(&puzzles_filename)) {
+        for puzzle in load_puzzles(filename)? {
+            puzzles.insert(puzzle.date, puzzle);
+        }
+    }
+
+    Ok(puzzles.into_values().collect())
+}
+
+fn count_words(puzzles: &[ScheduledPuzzle]) -> HashMap<String, Vec<usize>> {
     let mut words = HashMap::<String, Vec<usize>>::new();
 
-    for (puzzle_num, grid) in puzzles.iter().enumerate() {
+    for puzzle in puzzles.iter() {
+        let puzzle_num = puzzle.puzzle_number();
+        let grid = &puzzle.grid;
+
         for word in grid::WordPositions::new().map(|positions| {
             positions.map(|position| grid.solution.letters[position])
                 .collect::<String>()
@@ -91,7 +109,9 @@ where
 }
 
 fn main() -> ExitCode {
-    let Ok(puzzles) = load_puzzles()
+    let cli = Cli::parse();
+
+    let Ok(puzzles) = load_all_puzzles(&cli)
     else {
         return ExitCode::FAILURE;
     };
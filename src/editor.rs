@@ -31,9 +31,17 @@ mod word_search;
 mod generate_puzzle;
 mod wildcard;
 mod trie_builder;
+mod date;
+mod schedule;
+mod definitions;
+mod stars;
+mod rules;
+mod word_query;
+mod search_rank;
+mod fill;
+mod journal;
 
 use std::process::ExitCode;
-use letter_grid::LetterGrid;
 use grid::{WORD_LENGTH, N_LETTERS, N_WORDS};
 use dictionary::Dictionary;
 use std::ffi::c_int;
@@ -41,17 +49,69 @@ use std::sync::{Arc, mpsc};
 use std::thread;
 use word_grid::WordGrid;
 use grid_solver::GridSolver;
-use std::io::{BufRead, Write};
+use std::io::Write;
+use std::time::{Duration, Instant};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+use clap::Parser;
 use rand::Rng;
 use rand::seq::SliceRandom;
 use grid::{Grid, SolutionGrid, PuzzleGrid, PuzzleSquareState};
 use word_counter::WordCounter;
 use solver_state::{SolverState, SolverStatePair};
-use chrono::{naive::Days, NaiveDate};
 use wildcard::is_wildcard;
+use schedule::ScheduledPuzzle;
+use definitions::Definitions;
+use search_rank::{Frequencies, RankedWord};
+use journal::Journal;
 
 // Number of swaps to make when shuffling the puzzle
 const N_SHUFFLE_SWAPS: usize = 10;
+// Maximum number of changes that can be undone for each puzzle
+const MAX_UNDO_STEPS: usize = 100;
+// How long to wait after a change before saving the files
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(30);
+// Number of lines in the checklist of rules
+const CHECKLIST_LENGTH: usize = 4;
+
+#[derive(Parser)]
+#[command(name = "editor")]
+struct Cli {
+    #[arg(short, long, value_name = "FILE")]
+    puzzles: Option<OsString>,
+    #[arg(short, long, value_name = "FILE")]
+    dictionary: Option<OsString>,
+    #[arg(long, value_name = "FILE")]
+    definitions: Option<OsString>,
+    #[arg(long, value_name = "FILE",
+          help = "Other puzzles file to include when counting how often \
+                  words have been used. Can be given more than once.")]
+    history: Vec<OsString>,
+    #[arg(long, value_name = "FILE",
+          help = "File of word frequencies to use when ranking the \
+                  search results. Each line is a word and a count.")]
+    frequencies: Option<OsString>,
+}
+
+// Everything that is loaded from the files when the editor starts
+struct LoadedData {
+    puzzles: Vec<ScheduledPuzzle>,
+    history: Vec<ScheduledPuzzle>,
+    definitions: Definitions,
+    frequencies: Option<Frequencies>,
+}
+
+// The files that the editor saves to
+struct Files {
+    puzzles: PathBuf,
+    definitions: PathBuf,
+    // Every change is added to the journal as it is made so that it
+    // can be recovered if the editor doesn’t quit normally. The
+    // journal is emptied whenever the files are saved and removed
+    // when the editor quits.
+    journal: PathBuf,
+}
 
 const WRONG_LETTER_COLOR: i16 = 1;
 const FIRST_STATE_COLOR: i16 = 2;
@@ -80,12 +140,60 @@ struct Word {
     text: String,
 }
 
+// A state of a puzzle that can be restored with undo or redo
+#[derive(Clone, PartialEq, Eq)]
+struct UndoState {
+    grid: Grid,
+    added_letters: u32,
+}
+
+#[derive(Default)]
+struct History {
+    undo: Vec<UndoState>,
+    redo: Vec<UndoState>,
+    // Whether the puzzle was added with Ctrl+N in this session. If so
+    // then undoing past its first change removes it.
+    created: bool,
+    // Whether the oldest undo states were dropped because there were
+    // too many. The puzzle can’t be removed by undoing in that case
+    // because the bottom of the stack is no longer its first change.
+    truncated: bool,
+}
+
+// The definition that is being typed at the bottom of the screen
+struct DefinitionEdit {
+    word: String,
+    text: String,
+}
+
+// What to do with the date typed at the bottom of the screen
+#[derive(Clone, Copy)]
+enum DateAction {
+    Move,
+    Swap,
+    Duplicate,
+    Remove,
+}
+
+// A date that is being typed at the bottom of the screen
+struct DateEdit {
+    action: DateAction,
+    text: String,
+}
+
+// The word usage browser that is shown instead of the grids
+struct WordBrowser {
+    query: String,
+    sort: word_query::Sort,
+    selected: usize,
+}
+
 #[derive(Default)]
 enum SearchResults {
     #[default]
     None,
     Crosswords(Vec<crossword_solver::Crossword>),
-    Words(Vec<String>),
+    Words(Vec<RankedWord>),
 }
 
 struct Editor {
@@ -96,7 +204,12 @@ struct Editor {
     grid_x: i32,
     grid_y: i32,
     current_puzzle: usize,
-    puzzles: Vec<Grid>,
+    puzzles: Vec<ScheduledPuzzle>,
+    // Puzzles from the --history files. These are only used to count
+    // the words.
+    history: Vec<ScheduledPuzzle>,
+    // Undo history for each puzzle in the same order as `puzzles`
+    histories: Vec<History>,
     cursor_x: i32,
     cursor_y: i32,
     edit_direction: EditDirection,
@@ -107,18 +220,55 @@ struct Editor {
     solutions: Vec<WordGrid>,
     had_all_solutions: bool,
     shortest_swap_solution: Option<usize>,
+    // Set when the swap solver has finished without finding a solution
+    no_swap_solution: bool,
+    // Problems with the words found by rules::check_words, or None if
+    // the check hasn’t finished yet
+    word_problems: Option<Vec<rules::Problem>>,
+    // Set if the solver couldn’t make a letter grid from the puzzle
+    grid_error: Option<letter_grid::ParseError>,
     word_counter: WordCounter,
     search_results: SearchResults,
+    // The cursor position when the search results were made so that
+    // a result can be put in the right word
+    search_cursor: (i32, i32),
+    // Index of the selected search result in the order of
+    // search_result_words
+    selected_result: Option<usize>,
     added_letters: u32,
-    // Number of puzzles when the data was loaded
-    initial_n_puzzles: usize,
     last_key_was_letter: bool,
+    definitions: Definitions,
+    definitions_changed: bool,
+    frequencies: Option<Frequencies>,
+    definition_edit: Option<DefinitionEdit>,
+    date_edit: Option<DateEdit>,
+    word_browser: Option<WordBrowser>,
+    // The candidates for each word when the fill assistant is shown
+    fill_candidates: Option<fill::Candidates>,
+    files: Files,
+    journal: Journal,
+    // The puzzles as they were when the journal was last updated
+    journaled_puzzles: Vec<ScheduledPuzzle>,
+    // The time of the first change since the files were last saved
+    unsaved_since: Option<Instant>,
+    // Message to show at the bottom of the screen until the next key
+    status_message: Option<String>,
 }
 
 enum SolutionEventKind {
     Grid(WordGrid),
     GridEnd,
+    GridError(letter_grid::ParseError),
+    WordProblems(Vec<rules::Problem>),
     SwapSolution(usize),
+    NoSwapSolution,
+}
+
+// The state of an item in the checklist of rules for the puzzle
+enum CheckState {
+    Pending,
+    Passed,
+    Failed(String),
 }
 
 struct SolutionEvent {
@@ -270,26 +420,152 @@ fn color_for_state(state: PuzzleSquareState) -> i16 {
     state as i16 + FIRST_STATE_COLOR
 }
 
-fn date_string_for_puzzle(puzzle_num: usize) -> String {
-    let start_date = NaiveDate::from_ymd_opt(2023, 8, 7).unwrap();
+// Shows how many words can fit in a slot. A slot with no candidates
+// is a dead end so it is highlighted in red, and if there is only one
+// candidate then it is shown in green.
+fn draw_fill_candidates(candidates: &[String]) {
+    match candidates {
+        [] => {
+            let color = ncurses::COLOR_PAIR(WRONG_LETTER_COLOR);
+            ncurses::attron(color);
+            ncurses::attron(ncurses::A_BOLD());
+            ncurses::addstr(" no options");
+            ncurses::attroff(ncurses::A_BOLD());
+            ncurses::attroff(color);
+        },
+        [word] => {
+            let color = ncurses::COLOR_PAIR(
+                color_for_state(PuzzleSquareState::Correct)
+            );
+            ncurses::attron(color);
+            ncurses::addstr(&format!(" → {}", word.to_uppercase()));
+            ncurses::attroff(color);
+        },
+        _ => {
+            ncurses::addstr(&format!(" {} options", candidates.len()));
+        },
+    }
+}
+
+// Returns the grid positions of the horizontal or vertical word that
+// goes through the given square
+fn word_positions_through(x: i32, y: i32, horizontal: bool) -> Vec<usize> {
+    (0..WORD_LENGTH).map(|i| {
+        if horizontal {
+            y as usize * WORD_LENGTH + i
+        } else {
+            i * WORD_LENGTH + x as usize
+        }
+    }).collect()
+}
+
+fn is_grid_square(x: i32, y: i32) -> bool {
+    x >= 0
+        && x < WORD_LENGTH as i32
+        && y >= 0
+        && y < WORD_LENGTH as i32
+        && !grid::is_gap_space(x, y)
+}
+
+// Works out where each word in a list of search results is drawn.
+// Returns the screen position of each word that fits on the screen
+// and the number of lines used.
+fn layout_search_words<T: AsRef<str>>(
+    start_x: i32,
+    start_y: i32,
+    words: &[T],
+) -> (Vec<(i32, i32)>, i32) {
+    let max_x = ncurses::getmaxx(ncurses::stdscr());
+    let max_y = ncurses::getmaxy(ncurses::stdscr());
+
+    let mut x = start_x;
+    let mut y = start_y;
+    let mut positions = Vec::new();
+
+    for word in words.iter() {
+        let width = word.as_ref().chars().count() as i32 + 1;
+
+        if x + width > max_x {
+            x = start_x;
+            y += 1;
+
+            if y >= max_y {
+                break;
+            }
+        }
+
+        positions.push((x + 1, y));
+        x += width;
+    }
+
+    (positions, y - start_y + 1)
+}
+
+// Returns the index of the search result drawn at the screen position
+fn search_word_at<T: AsRef<str>>(
+    words: &[T],
+    positions: &[(i32, i32)],
+    x: i32,
+    y: i32,
+) -> Option<usize> {
+    words.iter().zip(positions.iter()).position(|(word, &(word_x, word_y))| {
+        word_y == y
+            && x >= word_x
+            && x < word_x + word.as_ref().chars().count() as i32
+    })
+}
+
+fn ranked_word_text(word: &RankedWord) -> String {
+    format!("{}:{}", word.word, word.score)
+}
+
+// Today’s date in the local time zone
+fn today() -> date::Date {
+    let tm = unsafe {
+        let now = libc::time(std::ptr::null_mut());
+        let mut tm = std::mem::zeroed::<libc::tm>();
+        libc::localtime_r(&now, &mut tm);
+        tm
+    };
+
+    date::Date::new(tm.tm_year + 1900, tm.tm_mon as u32 + 1, tm.tm_mday as u32)
+}
 
-    match start_date.checked_add_days(Days::new(puzzle_num as u64)) {
-        None => "?".to_string(),
-        Some(puzzle_date) => puzzle_date.format("%a %Y-%m-%d").to_string(),
+impl DateAction {
+    fn prompt(self) -> &'static str {
+        match self {
+            DateAction::Move => "Move this puzzle to",
+            DateAction::Swap => "Swap this puzzle with",
+            DateAction::Duplicate => "Copy this puzzle to",
+            DateAction::Remove => "Remove the puzzle for",
+        }
     }
 }
 
+fn date_string(date: date::Date) -> String {
+    static WEEKDAY_NAMES: [&str; date::N_WEEKDAYS] = [
+        "Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun",
+    ];
+
+    format!("{} {}", WEEKDAY_NAMES[date.weekday()], date)
+}
+
 impl Editor {
     fn new(
-        puzzles: Vec<Grid>,
+        data: LoadedData,
+        files: Files,
+        journal: Journal,
         dictionary: Arc<Dictionary>,
         solver_state: Arc<SolverStatePair>,
         grid_x: i32,
         grid_y: i32,
     ) -> Editor {
+        let LoadedData { puzzles, history, definitions, frequencies } = data;
+
         assert!(!puzzles.is_empty());
 
-        let initial_n_puzzles = puzzles.len();
+        let histories = puzzles.iter().map(|_| History::default()).collect();
+        let journaled_puzzles = puzzles.clone();
 
         let mut editor = Editor {
             dictionary,
@@ -300,6 +576,8 @@ impl Editor {
             grid_y,
             current_puzzle: 0,
             puzzles,
+            history,
+            histories,
             cursor_x: 0,
             cursor_y: 0,
             edit_direction: EditDirection::Right,
@@ -310,11 +588,27 @@ impl Editor {
             solutions: Vec::new(),
             had_all_solutions: false,
             shortest_swap_solution: None,
+            no_swap_solution: false,
+            word_problems: None,
+            grid_error: None,
             word_counter: WordCounter::new(),
             search_results: SearchResults::None,
+            search_cursor: (0, 0),
+            selected_result: None,
             added_letters: u32::MAX,
-            initial_n_puzzles,
             last_key_was_letter: false,
+            definitions,
+            definitions_changed: false,
+            frequencies,
+            definition_edit: None,
+            date_edit: None,
+            word_browser: None,
+            fill_candidates: None,
+            files,
+            journal,
+            journaled_puzzles,
+            unsaved_since: None,
+            status_message: None,
         };
 
         editor.update_words();
@@ -326,7 +620,14 @@ impl Editor {
 
     fn redraw(&self) {
         ncurses::clear();
-        let grid = &self.puzzles[self.current_puzzle];
+
+        if let Some(ref browser) = self.word_browser {
+            self.draw_word_browser(browser);
+            ncurses::refresh();
+            return;
+        }
+
+        let grid = &self.puzzles[self.current_puzzle].grid;
 
         draw_grid(
             grid,
@@ -340,10 +641,7 @@ impl Editor {
             EditDirection::Down => 'v',
         };
 
-        let right_side = self.grid_x
-            + puzzle_x()
-            + WORD_LENGTH as i32
-            + 5;
+        let right_side = self.right_side();
 
         ncurses::mvaddch(self.grid_y, right_side, direction_ch as u32);
 
@@ -351,33 +649,33 @@ impl Editor {
             " {}/{} {}",
             self.current_puzzle + 1,
             self.puzzles.len(),
-            date_string_for_puzzle(self.current_puzzle),
+            date_string(self.puzzles[self.current_puzzle].date),
         ));
 
-        if self.current_puzzle >= self.initial_n_puzzles {
+        if self.histories[self.current_puzzle].created {
+            let n_created = self.histories[0..=self.current_puzzle].iter()
+                .filter(|history| history.created)
+                .count();
+
+            ncurses::addstr(&format!(" +{}", n_created));
+        }
+
+        let history = &self.histories[self.current_puzzle];
+
+        if !history.undo.is_empty() || !history.redo.is_empty() {
             ncurses::addstr(&format!(
-                " +{}",
-                self.current_puzzle - self.initial_n_puzzles + 1,
+                " undo:{} redo:{}",
+                history.undo.len(),
+                history.redo.len(),
             ));
         }
 
         self.draw_words(right_side, self.grid_y + 2);
-        self.draw_search_results(
-            right_side,
-            self.grid_y + 2 + N_WORDS as i32 + 2,
-        );
+        self.draw_checklist(right_side, self.grid_y + 2 + N_WORDS as i32 + 2);
+        self.draw_search_results(right_side, self.search_results_y());
 
         let mut y = self.grid_y + WORD_LENGTH as i32 + 3;
 
-        if let Some(n_swaps) = self.shortest_swap_solution {
-            ncurses::mvaddstr(
-                y,
-                self.grid_x,
-                &format!("Minimum swaps: {}", n_swaps),
-            );
-            y += 2;
-        }
-
         if !self.solutions.is_empty() {
             ncurses::mvaddstr(y, self.grid_x, "Solutions");
 
@@ -431,13 +729,141 @@ impl Editor {
             }
         }
 
-        self.position_cursor();
+        if let Some(ref edit) = self.definition_edit {
+            let max_y = ncurses::getmaxy(ncurses::stdscr());
+
+            ncurses::mv(max_y - 1, 0);
+            ncurses::clrtoeol();
+            ncurses::addstr(&format!(
+                "Definition of {}: {}",
+                edit.word,
+                edit.text,
+            ));
+        } else if let Some(ref edit) = self.date_edit {
+            let max_y = ncurses::getmaxy(ncurses::stdscr());
+
+            ncurses::mv(max_y - 1, 0);
+            ncurses::clrtoeol();
+            ncurses::addstr(&format!(
+                "{}: {}",
+                edit.action.prompt(),
+                edit.text,
+            ));
+        } else {
+            if let Some(ref message) = self.status_message {
+                let max_y = ncurses::getmaxy(ncurses::stdscr());
+
+                ncurses::mv(max_y - 1, 0);
+                ncurses::clrtoeol();
+                ncurses::addstr(message);
+            }
+
+            self.position_cursor();
+        }
 
         ncurses::refresh();
     }
 
+    fn draw_word_browser(&self, browser: &WordBrowser) {
+        let max_y = ncurses::getmaxy(ncurses::stdscr());
+        let wrong_letter_color = ncurses::COLOR_PAIR(WRONG_LETTER_COLOR);
+
+        ncurses::mvaddstr(
+            0,
+            0,
+            &format!("Word usage, sorted by {}", browser.sort.name()),
+        );
+        ncurses::mvaddstr(
+            max_y - 1,
+            0,
+            "Enter: go to last use  Tab: sort  Esc: close",
+        );
+
+        let entries = match self.word_browser_entries(browser) {
+            Ok(entries) => entries,
+            Err(e) => {
+                ncurses::mvaddstr(4, 0, &e.to_string());
+                Vec::new()
+            },
+        };
+
+        ncurses::mvaddstr(
+            1,
+            0,
+            &format!("{} words", entries.len()),
+        );
+
+        // Keep the selected word in the middle of the screen
+        let n_lines = (max_y - 5).max(1) as usize;
+        let first = browser.selected
+            .min(entries.len().saturating_sub(1))
+            .saturating_sub(n_lines / 2);
+
+        for (i, entry) in entries.iter().skip(first).take(n_lines).enumerate() {
+            let selected = first + i == browser.selected;
+
+            if selected {
+                ncurses::attron(ncurses::A_REVERSE());
+            }
+
+            ncurses::mvaddstr(
+                4 + i as i32,
+                0,
+                &format!("{:<12} {:>3} ", entry.word, entry.count),
+            );
+
+            if selected {
+                ncurses::attroff(ncurses::A_REVERSE());
+            }
+
+            let too_new = self.last_use_is_too_new(entry.last_use);
+
+            if too_new {
+                ncurses::attron(wrong_letter_color);
+            }
+
+            ncurses::addstr(&format!("#{}", entry.last_use + 1));
+
+            if too_new {
+                ncurses::attroff(wrong_letter_color);
+            }
+
+            ncurses::addstr(&format!(
+                " {}",
+                date_string(date::Date::for_puzzle(entry.last_use)),
+            ));
+        }
+
+        ncurses::mvaddstr(2, 0, &format!("Search: {}", browser.query));
+    }
+
+    // The x position of the things drawn to the right of the grids
+    fn right_side(&self) -> i32 {
+        self.grid_x
+            + puzzle_x()
+            + WORD_LENGTH as i32
+            + 5
+    }
+
+    fn search_results_y(&self) -> i32 {
+        self.grid_y + 2 + N_WORDS as i32 + 2 + CHECKLIST_LENGTH as i32 + 1
+    }
+
+    fn word_browser_entries(
+        &self,
+        browser: &WordBrowser,
+    ) -> Result<Vec<word_query::Entry<'_>>, word_query::ParseError> {
+        let query = browser.query.parse::<word_query::Query>()?;
+
+        Ok(word_query::search(&self.word_counter, &query, browser.sort))
+    }
+
+    fn current_puzzle_number(&self) -> usize {
+        self.puzzles[self.current_puzzle].puzzle_number()
+    }
+
     fn last_use_is_too_new(&self, last_use: usize) -> bool {
-        last_use + 30 > self.current_puzzle
+        last_use + 30 > self.current_puzzle_number()
     }
 
     fn draw_words(&self, x: i32, y: i32) {
@@ -460,6 +886,12 @@ impl Editor {
                 }
             );
 
+            if let Some(ref candidates) = self.fill_candidates {
+                if word.text.chars().any(is_wildcard) {
+                    draw_fill_candidates(&candidates[i]);
+                }
+            }
+
             for (word, count, last_use)
                 in self.word_counter.counts(&word.text)
             {
@@ -479,6 +911,94 @@ impl Editor {
 
                 ncurses::addch(')' as u32);
             }
+
+            if self.definitions.get(&word.text).is_some() {
+                ncurses::addstr(" 📖");
+            }
+        }
+    }
+
+    // The result of each of the rules that check-puzzles checks for
+    // the current puzzle
+    fn checklist(&self) -> [(&'static str, CheckState); CHECKLIST_LENGTH] {
+        let word_check = |filter: fn(&rules::Problem) -> Option<&str>| {
+            match self.word_problems {
+                None => CheckState::Pending,
+                Some(ref problems) => {
+                    let words = problems.iter()
+                        .filter_map(filter)
+                        .collect::<Vec<_>>();
+
+                    if words.is_empty() {
+                        CheckState::Passed
+                    } else {
+                        CheckState::Failed(words.join(", "))
+                    }
+                },
+            }
+        };
+
+        let problem_state = |problem: Option<rules::Problem>| {
+            match problem {
+                Some(problem) => CheckState::Failed(problem.to_string()),
+                None => CheckState::Passed,
+            }
+        };
+
+        let solutions = if let Some(ref e) = self.grid_error {
+            CheckState::Failed(e.to_string())
+        } else if self.had_all_solutions {
+            problem_state(rules::check_solution_count(self.solutions.len()))
+        } else if self.solutions.len() > 1 {
+            CheckState::Failed(format!(
+                "at least {} solutions",
+                self.solutions.len(),
+            ))
+        } else {
+            CheckState::Pending
+        };
+
+        let swaps = if let Some(n_swaps) = self.shortest_swap_solution {
+            problem_state(rules::check_minimum_swaps(Some(n_swaps)))
+        } else if self.no_swap_solution {
+            problem_state(rules::check_minimum_swaps(None))
+        } else {
+            CheckState::Pending
+        };
+
+        [
+            (
+                "Words in dictionary",
+                word_check(|problem| match problem {
+                    rules::Problem::BadWord(word) => Some(word),
+                    _ => None,
+                }),
+            ),
+            (
+                "No repeated stems",
+                word_check(|problem| match problem {
+                    rules::Problem::DuplicateWord(word) => Some(word),
+                    _ => None,
+                }),
+            ),
+            ("One solution", solutions),
+            ("Minimum swaps", swaps),
+        ]
+    }
+
+    fn draw_checklist(&self, x: i32, y: i32) {
+        for (i, (name, state)) in self.checklist().into_iter().enumerate() {
+            ncurses::mvaddstr(y + i as i32, x, match state {
+                CheckState::Pending => "⏳",
+                CheckState::Passed => "✅",
+                CheckState::Failed(_) => "❌",
+            });
+            ncurses::addch(' ' as u32);
+            ncurses::addstr(name);
+
+            if let CheckState::Failed(message) = state {
+                ncurses::addstr(&format!(": {}", message));
+            }
         }
     }
 
@@ -494,37 +1014,33 @@ impl Editor {
         }
     }
 
+    // Draws the words and returns the number of lines used. `first`
+    // is the index of the first word in the order of
+    // search_result_words so that the selected one can be highlighted.
     fn draw_search_words<T: AsRef<str>>(
         &self,
         start_x: i32,
         start_y: i32,
         words: &[T],
+        first: usize,
     ) -> i32 {
-        let max_x = ncurses::getmaxx(ncurses::stdscr());
-        let max_y = ncurses::getmaxy(ncurses::stdscr());
+        let (positions, n_lines) = layout_search_words(start_x, start_y, words);
 
-        let mut x = start_x;
-        let mut y = start_y;
+        for (i, (word, (x, y))) in words.iter().zip(positions).enumerate() {
+            let selected = self.selected_result == Some(first + i);
 
-        ncurses::mv(start_y, start_x);
-
-        for word in words.iter() {
-            if x + WORD_LENGTH as i32 + 1 > max_x {
-                x = start_x;
-                y += 1;
+            if selected {
+                ncurses::attron(ncurses::A_REVERSE());
+            }
 
-                if y >= max_y {
-                    break;
-                }
+            ncurses::mvaddstr(y, x, word.as_ref());
 
-                ncurses::mv(y, x);
+            if selected {
+                ncurses::attroff(ncurses::A_REVERSE());
             }
-            ncurses::addch(' ' as u32);
-            ncurses::addstr(word.as_ref());
-            x += WORD_LENGTH as i32 + 1;
         }
 
-        y - start_y + 1
+        n_lines
     }
 
     fn draw_crosswords(
@@ -538,6 +1054,8 @@ impl Editor {
         ncurses::mvaddstr(y, start_x, "Crosswords:");
         y += 2;
 
+        let mut first = 0;
+
         for crossword in crosswords.iter() {
             if y >= max_y {
                 break;
@@ -547,19 +1065,24 @@ impl Editor {
             addch_utf8(crossword.cross_letter);
             ncurses::addch(':' as u32);
 
-            y += self.draw_search_words(start_x + 2, y, &crossword.a_words);
-            y += self.draw_search_words(start_x + 2, y, &crossword.b_words);
+            for words in [&crossword.a_words, &crossword.b_words] {
+                y += self.draw_search_words(start_x + 2, y, words, first);
+                first += words.len();
+            }
         }
     }
 
-    fn draw_words_results<T: AsRef<str>>(
+    fn draw_words_results(
         &self,
-        words: &[T],
+        words: &[RankedWord],
         x: i32,
         y: i32,
     ) {
         ncurses::mvaddstr(y, x, "Search results:");
-        self.draw_search_words(x - 1, y + 2, words);
+
+        let words = words.iter().map(ranked_word_text).collect::<Vec<_>>();
+
+        self.draw_search_words(x - 1, y + 2, &words, 0);
     }
 
     fn position_cursor(&self) {
@@ -658,7 +1181,7 @@ impl Editor {
         let position = self.cursor_x as usize
             + self.cursor_y as usize * WORD_LENGTH;
 
-        let grid = &mut self.puzzles[self.current_puzzle];
+        let grid = &mut self.puzzles[self.current_puzzle].grid;
 
         grid.solution.letters[position] = ch;
         if is_wildcard(ch) {
@@ -675,7 +1198,7 @@ impl Editor {
     }
 
     fn score_state_for_find(&self, pos: usize) -> u8 {
-        let grid = &self.puzzles[self.current_puzzle];
+        let grid = &self.puzzles[self.current_puzzle].grid;
 
         match grid.puzzle.squares[pos].state {
             PuzzleSquareState::Correct => 0,
@@ -687,7 +1210,7 @@ impl Editor {
     fn find_letter(&self, ch: char) -> Option<usize> {
         let mut best_letter = None;
 
-        let grid = &self.puzzles[self.current_puzzle];
+        let grid = &self.puzzles[self.current_puzzle].grid;
 
         for (i, square) in grid.puzzle.squares.iter().enumerate() {
             if i == self.cursor_pos() {
@@ -726,7 +1249,7 @@ impl Editor {
             };
 
             let cursor_pos = self.cursor_pos();
-            let grid = &mut self.puzzles[self.current_puzzle];
+            let grid = &mut self.puzzles[self.current_puzzle].grid;
             grid.puzzle.squares.swap(pos, cursor_pos);
             grid.update_square_states();
             self.advance_cursor();
@@ -753,10 +1276,174 @@ impl Editor {
             ncurses::KEY_PPAGE => self.move_between_puzzles(-1),
             ncurses::KEY_HOME => self.set_current_puzzle(0),
             ncurses::KEY_END => self.set_current_puzzle(self.puzzles.len() - 1),
+            ncurses::KEY_MOUSE => self.handle_mouse(),
             _ => (),
         }
     }
 
+    fn handle_mouse(&mut self) {
+        let mut event = ncurses::MEVENT { id: 0, x: 0, y: 0, z: 0, bstate: 0 };
+
+        if ncurses::getmouse(&mut event) != ncurses::OK
+            || event.bstate & ncurses::BUTTON1_PRESSED as ncurses::mmask_t == 0
+        {
+            return;
+        }
+
+        let x = event.x - self.grid_x;
+        let y = event.y - self.grid_y - 1;
+
+        if is_grid_square(x, y) {
+            self.cursor_x = x;
+            self.cursor_y = y;
+            self.current_grid = GridChoice::Solution;
+            self.position_cursor();
+            ncurses::refresh();
+        } else if is_grid_square(x - puzzle_x(), y) {
+            self.cursor_x = x - puzzle_x();
+            self.cursor_y = y;
+            self.current_grid = GridChoice::Puzzle;
+
+            // The first click selects a letter and the second click
+            // swaps it with another one
+            match self.selected_position {
+                Some(pos) if pos == self.cursor_pos() => {
+                    self.selected_position = None;
+                    self.redraw();
+                },
+                Some(_) => self.handle_swap(),
+                None => self.handle_mark(),
+            }
+        } else if let Some(index) = self.search_result_at(event.x, event.y) {
+            self.selected_result = Some(index);
+            self.insert_search_result();
+        }
+    }
+
+    // Returns all of the words in the search results in the order
+    // that they are drawn. Each word is given with whether it goes in
+    // the horizontal word or the vertical word at the place where the
+    // search was made.
+    fn search_result_words(&self) -> Vec<(&str, bool)> {
+        match self.search_results {
+            SearchResults::None => Vec::new(),
+            SearchResults::Crosswords(ref crosswords) => {
+                // The a_words go across and the b_words go down
+                crosswords.iter().flat_map(|crossword| {
+                    crossword.a_words.iter()
+                        .map(|word| (word.as_str(), true))
+                        .chain(crossword.b_words.iter()
+                               .map(|word| (word.as_str(), false)))
+                }).collect()
+            },
+            SearchResults::Words(ref words) => {
+                let horizontal = self.search_cursor.1 & 1 == 0;
+
+                words.iter()
+                    .map(|word| (word.word.as_str(), horizontal))
+                    .collect()
+            },
+        }
+    }
+
+    // Returns the index of the search result drawn at the screen
+    // position in the order of search_result_words
+    fn search_result_at(&self, x: i32, y: i32) -> Option<usize> {
+        let right_side = self.right_side();
+        let results_y = self.search_results_y();
+
+        match self.search_results {
+            SearchResults::None => None,
+            SearchResults::Crosswords(ref crosswords) => {
+                let max_y = ncurses::getmaxy(ncurses::stdscr());
+                let mut line = results_y + 2;
+                let mut first = 0;
+
+                for crossword in crosswords.iter() {
+                    if line >= max_y {
+                        break;
+                    }
+
+                    for words in [&crossword.a_words, &crossword.b_words] {
+                        let (positions, n_lines) =
+                            layout_search_words(right_side + 2, line, words);
+
+                        if let Some(index) =
+                            search_word_at(words, &positions, x, y)
+                        {
+                            return Some(first + index);
+                        }
+
+                        line += n_lines;
+                        first += words.len();
+                    }
+                }
+
+                None
+            },
+            SearchResults::Words(ref words) => {
+                let texts = words.iter()
+                    .map(ranked_word_text)
+                    .collect::<Vec<_>>();
+                let (positions, _) =
+                    layout_search_words(right_side - 1, results_y + 2, &texts);
+
+                search_word_at(&texts, &positions, x, y)
+            },
+        }
+    }
+
+    fn move_selected_result(&mut self, offset: isize) {
+        let n_results = self.search_result_words().len();
+
+        if n_results == 0 {
+            return;
+        }
+
+        self.selected_result = Some(match self.selected_result {
+            Some(index) => {
+                (index as isize + offset).rem_euclid(n_results as isize)
+                    as usize
+            },
+            None if offset < 0 => n_results - 1,
+            None => 0,
+        });
+
+        self.redraw();
+    }
+
+    // Writes the selected search result into the solution grid
+    fn insert_search_result(&mut self) {
+        let Some(index) = self.selected_result
+        else {
+            return;
+        };
+        let Some(&(word, horizontal)) = self.search_result_words().get(index)
+        else {
+            return;
+        };
+
+        let (cursor_x, cursor_y) = self.search_cursor;
+        let positions = word_positions_through(cursor_x, cursor_y, horizontal);
+        let letters = word.chars()
+            .filter_map(|ch| ch.to_uppercase().next())
+            .collect::<Vec<_>>();
+
+        self.save_undo_state();
+
+        let grid = &mut self.puzzles[self.current_puzzle].grid;
+
+        for (pos, ch) in positions.into_iter().zip(letters) {
+            grid.solution.letters[pos] = ch;
+            self.added_letters |= 1 << pos;
+        }
+
+        grid.update_square_states();
+        self.update_words();
+        self.send_grid();
+        self.redraw();
+    }
+
     fn cursor_pos(&self) -> usize {
         self.cursor_x as usize + self.cursor_y as usize * WORD_LENGTH
     }
@@ -772,8 +1459,9 @@ impl Editor {
     fn handle_swap(&mut self) {
         if matches!(self.current_grid, GridChoice::Puzzle) {
             if let Some(pos) = self.selected_position {
+                self.save_undo_state();
                 let cursor_pos = self.cursor_pos();
-                let grid = &mut self.puzzles[self.current_puzzle];
+                let grid = &mut self.puzzles[self.current_puzzle].grid;
                 grid.puzzle.squares.swap(pos, cursor_pos);
                 grid.update_square_states();
                 self.selected_position = None;
@@ -789,6 +1477,10 @@ impl Editor {
             '$' => self.toggle_edit_direction(),
             ' ' => self.handle_mark(),
             '\u{0003}' => self.should_quit = true, // Ctrl+C
+            '\u{0002}' => self.start_word_browser(), // Ctrl+B
+            '\u{0004}' => self.start_definition_edit(), // Ctrl+D
+            '\u{001a}' => self.undo(), // Ctrl+Z
+            '\u{0019}' => self.redo(), // Ctrl+Y
             '\u{0007}' => self.generate_puzzle(), // Ctrl+G
             '\u{0010}' => self.pattern_search(), // Ctrl+P
             '\u{0012}' => self.shuffle_puzzle(), // Ctrl+R
@@ -800,6 +1492,15 @@ impl Editor {
             '\u{0016}' => self.remove_invalid_search_results(), // Ctrl+V
             '\u{000e}' => self.new_puzzle(), // Ctrl+N
             '\u{0018}' => self.find_crosswords(), // Ctrl+X
+            '\u{0001}' => self.toggle_fill_assistant(), // Ctrl+A
+            '\u{0006}' => self.fill_grid(), // Ctrl+F
+            '[' => self.move_selected_result(-1),
+            ']' => self.move_selected_result(1),
+            '\u{0017}' => self.insert_search_result(), // Ctrl+W
+            '\u{000f}' => self.start_date_edit(DateAction::Move), // Ctrl+O
+            '\u{0014}' => self.start_date_edit(DateAction::Swap), // Ctrl+T
+            '\u{0015}' => self.start_date_edit(DateAction::Duplicate), // Ctrl+U
+            '\u{000b}' => self.start_date_edit(DateAction::Remove), // Ctrl+K
             _ => return false,
         }
 
@@ -811,6 +1512,11 @@ impl Editor {
             if self.handle_char_shortcut(ch) {
                 self.last_key_was_letter = false;
             } else if ch.is_alphabetic() || ch == '.' {
+                // A run of typed letters is undone in one step
+                if !self.last_key_was_letter {
+                    self.save_undo_state();
+                }
+
                 match self.current_grid {
                     GridChoice::Solution => {
                         for ch in ch.to_uppercase() {
@@ -826,15 +1532,483 @@ impl Editor {
         }
     }
 
-    fn handle_key(&mut self, key: ncurses::WchResult) {
+    // The number of the word that the cursor is on in the order of
+    // grid::WordPositions. If the cursor is on an intersection then
+    // the edit direction decides which word to use.
+    fn cursor_word_num(&self) -> usize {
+        let across = self.cursor_y & 1 == 0
+            && (self.cursor_x & 1 == 1
+                || matches!(self.edit_direction, EditDirection::Right));
+
+        if across {
+            self.cursor_y as usize
+        } else {
+            self.cursor_x as usize + 1
+        }
+    }
+
+    fn start_definition_edit(&mut self) {
+        let word = &self.words[self.cursor_word_num()].text;
+
+        if word.chars().any(is_wildcard) {
+            return;
+        }
+
+        let text = self.definitions.get(word).unwrap_or("").to_string();
+
+        self.definition_edit = Some(DefinitionEdit {
+            word: word.clone(),
+            text,
+        });
+
+        self.redraw();
+    }
+
+    fn handle_definition_key(&mut self, key: ncurses::WchResult) {
+        let Some(edit) = self.definition_edit.as_mut()
+        else {
+            return;
+        };
+
+        match key {
+            ncurses::WchResult::KeyCode(ncurses::KEY_BACKSPACE) => {
+                edit.text.pop();
+            },
+            ncurses::WchResult::KeyCode(_) => return,
+            ncurses::WchResult::Char(ch) => {
+                match char::from_u32(ch as u32) {
+                    // Enter or Ctrl+J
+                    Some('\n') | Some('\r') => {
+                        self.definitions.set(&edit.word, &edit.text);
+                        self.definitions_changed = true;
+
+                        if let Err(e) = self.journal.add_line(
+                            &format!("D {} {}", edit.word, edit.text)
+                        ) {
+                            self.status_message = Some(e);
+                        }
+
+                        self.definition_edit = None;
+                        self.set_unsaved();
+                    },
+                    // Escape or Ctrl+C
+                    Some('\u{001b}') | Some('\u{0003}') => {
+                        self.definition_edit = None;
+                    },
+                    Some('\u{0008}') | Some('\u{007f}') => {
+                        edit.text.pop();
+                    },
+                    Some(ch) if !ch.is_control() => edit.text.push(ch),
+                    _ => return,
+                }
+            },
+        }
+
+        self.redraw();
+    }
+
+    fn start_date_edit(&mut self, action: DateAction) {
+        let date = match action {
+            DateAction::Duplicate => {
+                self.puzzles.last().unwrap().date.add_days(1)
+            },
+            DateAction::Move | DateAction::Swap | DateAction::Remove => {
+                self.puzzles[self.current_puzzle].date
+            },
+        };
+
+        self.date_edit = Some(DateEdit {
+            action,
+            text: date.to_string(),
+        });
+
+        self.redraw();
+    }
+
+    fn handle_date_key(&mut self, key: ncurses::WchResult) {
+        let Some(edit) = self.date_edit.as_mut()
+        else {
+            return;
+        };
+
+        match key {
+            ncurses::WchResult::KeyCode(ncurses::KEY_BACKSPACE) => {
+                edit.text.pop();
+            },
+            ncurses::WchResult::KeyCode(_) => return,
+            ncurses::WchResult::Char(ch) => {
+                match char::from_u32(ch) {
+                    // Enter or Ctrl+J
+                    Some('\n') | Some('\r') => {
+                        let action = edit.action;
+                        let result = match edit.text.trim().parse::<date::Date>() {
+                            Ok(date) => self.apply_date_action(action, date),
+                            Err(_) => Err(format!("invalid date: {}", edit.text)),
+                        };
+
+                        self.date_edit = None;
+
+                        if let Err(e) = result {
+                            self.status_message = Some(e);
+                        }
+                    },
+                    // Escape or Ctrl+C
+                    Some('\u{001b}') | Some('\u{0003}') => {
+                        self.date_edit = None;
+                    },
+                    Some('\u{0008}') | Some('\u{007f}') => {
+                        edit.text.pop();
+                    },
+                    Some(ch) if !ch.is_control() => edit.text.push(ch),
+                    _ => return,
+                }
+            },
+        }
+
+        self.redraw();
+    }
+
+    // Puzzles for today or earlier have already been published so
+    // they can’t be changed
+    fn check_unpublished(&self, date: date::Date) -> Result<(), String> {
+        if date.puzzle_number().is_none() {
+            Err(format!("{} is before the first puzzle", date))
+        } else if date <= today() {
+            Err(format!("the puzzle for {} is already published", date))
+        } else {
+            Ok(())
+        }
+    }
+
+    // Returns the index of the puzzle for the date, or the index
+    // where it would be inserted if there isn’t one
+    fn puzzle_index(&self, date: date::Date) -> Result<usize, usize> {
+        self.puzzles.binary_search_by_key(&date, |puzzle| puzzle.date)
+    }
+
+    fn apply_date_action(
+        &mut self,
+        action: DateAction,
+        date: date::Date,
+    ) -> Result<(), String> {
+        let current_date = self.puzzles[self.current_puzzle].date;
+
+        match action {
+            DateAction::Move => {
+                self.check_unpublished(current_date)?;
+                self.check_unpublished(date)?;
+
+                let Err(_) = self.puzzle_index(date)
+                else {
+                    return Err(format!("{} already has a puzzle", date));
+                };
+
+                let mut puzzle = self.puzzles.remove(self.current_puzzle);
+                let history = self.histories.remove(self.current_puzzle);
+                let index = self.puzzle_index(date).unwrap_err();
+
+                puzzle.date = date;
+                self.puzzles.insert(index, puzzle);
+                self.histories.insert(index, history);
+                self.current_puzzle = index;
+            },
+            DateAction::Swap => {
+                self.check_unpublished(current_date)?;
+                self.check_unpublished(date)?;
+
+                let Ok(index) = self.puzzle_index(date)
+                else {
+                    return Err(format!("there is no puzzle for {}", date));
+                };
+
+                // Swap everything except the dates so that the list
+                // stays in order
+                self.puzzles.swap(self.current_puzzle, index);
+                self.histories.swap(self.current_puzzle, index);
+                self.puzzles[self.current_puzzle].date = current_date;
+                self.puzzles[index].date = date;
+                self.current_puzzle = index;
+            },
+            DateAction::Duplicate => {
+                self.check_unpublished(date)?;
+
+                let Err(index) = self.puzzle_index(date)
+                else {
+                    return Err(format!("{} already has a puzzle", date));
+                };
+
+                let grid = self.puzzles[self.current_puzzle].grid.clone();
+
+                self.puzzles.insert(index, ScheduledPuzzle { date, grid });
+                self.histories.insert(
+                    index,
+                    History { created: true, ..Default::default() },
+                );
+                self.current_puzzle = index;
+            },
+            DateAction::Remove => {
+                self.check_unpublished(date)?;
+
+                let Ok(index) = self.puzzle_index(date)
+                else {
+                    return Err(format!("there is no puzzle for {}", date));
+                };
+
+                if self.puzzles.len() <= 1 {
+                    return Err("can’t remove the only puzzle".to_string());
+                }
+
+                self.current_puzzle = index;
+                self.remove_current_puzzle();
+
+                return Ok(());
+            },
+        }
+
+        self.load_current_puzzle();
+
+        Ok(())
+    }
+
+    fn start_word_browser(&mut self) {
+        self.word_browser = Some(WordBrowser {
+            query: String::new(),
+            sort: word_query::Sort::LeastRecentlyUsed,
+            selected: 0,
+        });
+
+        self.redraw();
+    }
+
+    // Switches to the puzzle where the selected word was last used
+    fn go_to_browser_selection(&mut self) {
+        let Some(ref browser) = self.word_browser
+        else {
+            return;
+        };
+
+        let last_use = match self.word_browser_entries(browser) {
+            Ok(entries) => {
+                let Some(entry) = entries.get(browser.selected)
+                else {
+                    return;
+                };
+                entry.last_use
+            },
+            Err(_) => return,
+        };
+
+        match self.puzzles.iter()
+            .position(|puzzle| puzzle.puzzle_number() == last_use)
+        {
+            Some(puzzle_num) => {
+                self.word_browser = None;
+                self.set_current_puzzle(puzzle_num);
+                self.redraw();
+            },
+            None => {
+                self.status_message = Some(format!(
+                    "Puzzle #{} is only in the history",
+                    last_use + 1,
+                ));
+                self.word_browser = None;
+                self.redraw();
+            },
+        }
+    }
+
+    fn handle_word_browser_key(&mut self, key: ncurses::WchResult) {
+        let n_entries = self.word_browser.as_ref()
+            .and_then(|browser| self.word_browser_entries(browser).ok())
+            .map(|entries| entries.len())
+            .unwrap_or(0);
+        let page = (ncurses::getmaxy(ncurses::stdscr()) - 5).max(1) as usize;
+
+        let Some(browser) = self.word_browser.as_mut()
+        else {
+            return;
+        };
+
+        match key {
+            ncurses::WchResult::KeyCode(ncurses::KEY_UP) => {
+                browser.selected = browser.selected.saturating_sub(1);
+            },
+            ncurses::WchResult::KeyCode(ncurses::KEY_DOWN) => {
+                browser.selected += 1;
+            },
+            ncurses::WchResult::KeyCode(ncurses::KEY_PPAGE) => {
+                browser.selected = browser.selected.saturating_sub(page);
+            },
+            ncurses::WchResult::KeyCode(ncurses::KEY_NPAGE) => {
+                browser.selected += page;
+            },
+            ncurses::WchResult::KeyCode(ncurses::KEY_HOME) => {
+                browser.selected = 0;
+            },
+            ncurses::WchResult::KeyCode(ncurses::KEY_END) => {
+                browser.selected = usize::MAX;
+            },
+            ncurses::WchResult::KeyCode(ncurses::KEY_BACKSPACE) => {
+                browser.query.pop();
+                browser.selected = 0;
+            },
+            ncurses::WchResult::KeyCode(_) => return,
+            ncurses::WchResult::Char(ch) => {
+                match char::from_u32(ch) {
+                    // Enter or Ctrl+J
+                    Some('\n') | Some('\r') => {
+                        self.go_to_browser_selection();
+                        return;
+                    },
+                    // Escape, Ctrl+C or Ctrl+B
+                    Some('\u{001b}') | Some('\u{0003}') | Some('\u{0002}') => {
+                        self.word_browser = None;
+                    },
+                    Some('\t') => browser.sort = browser.sort.next(),
+                    Some('\u{0008}') | Some('\u{007f}') => {
+                        browser.query.pop();
+                        browser.selected = 0;
+                    },
+                    Some(ch) if !ch.is_control() => {
+                        browser.query.extend(ch.to_uppercase());
+                        browser.selected = 0;
+                    },
+                    _ => return,
+                }
+            },
+        }
+
+        if let Some(ref mut browser) = self.word_browser {
+            browser.selected = browser.selected
+                .min(n_entries.saturating_sub(1));
+        }
+
+        self.redraw();
+    }
+
+    fn handle_key(&mut self, key: ncurses::WchResult) {
+        if self.status_message.take().is_some() {
+            self.redraw();
+        }
+
+        if self.word_browser.is_some() {
+            self.handle_word_browser_key(key);
+            return;
+        }
+
+        if self.definition_edit.is_some() {
+            self.handle_definition_key(key);
+            return;
+        }
+
+        if self.date_edit.is_some() {
+            self.handle_date_key(key);
+            return;
+        }
+
         match key {
             ncurses::WchResult::KeyCode(code) => self.handle_key_code(code),
             ncurses::WchResult::Char(ch) => self.handle_char(ch),
         }
     }
 
+    fn set_unsaved(&mut self) {
+        if self.unsaved_since.is_none() {
+            self.unsaved_since = Some(Instant::now());
+        }
+    }
+
+    // Adds the changes to the puzzles since the last time this was
+    // called to the journal
+    fn update_journal(&mut self) {
+        let journaled = self.journaled_puzzles.iter()
+            .map(|puzzle| (puzzle.date, &puzzle.grid))
+            .collect::<HashMap<_, _>>();
+        let mut lines = Vec::new();
+
+        for puzzle in self.puzzles.iter() {
+            if journaled.get(&puzzle.date) != Some(&&puzzle.grid) {
+                lines.push(format!("P {} {}", puzzle.date, puzzle.grid));
+            }
+        }
+
+        let dates = self.puzzles.iter()
+            .map(|puzzle| puzzle.date)
+            .collect::<HashSet<_>>();
+
+        for puzzle in self.journaled_puzzles.iter() {
+            if !dates.contains(&puzzle.date) {
+                lines.push(format!("R {}", puzzle.date));
+            }
+        }
+
+        if lines.is_empty() {
+            return;
+        }
+
+        self.set_unsaved();
+
+        match lines.iter().try_for_each(|line| self.journal.add_line(line)) {
+            Ok(()) => self.journaled_puzzles = self.puzzles.clone(),
+            Err(e) => {
+                // The changes will be written again after the next
+                // key. Replaying a line twice does no harm.
+                self.status_message = Some(e);
+                self.redraw();
+            },
+        }
+    }
+
+    // Saves the puzzles and the definitions and empties the journal
+    fn save(&mut self) -> Result<(), String> {
+        save_file(&self.files.puzzles, &schedule::to_string(&self.puzzles))?;
+
+        if self.definitions_changed {
+            save_file(&self.files.definitions, &self.definitions.to_string())?;
+            self.definitions_changed = false;
+        }
+
+        self.journal.clear();
+
+        self.unsaved_since = None;
+
+        Ok(())
+    }
+
+    // The number of milliseconds until the next autosave for poll, or
+    // -1 if there is nothing to save
+    fn autosave_timeout(&self) -> c_int {
+        match self.unsaved_since {
+            Some(unsaved_since) => {
+                AUTOSAVE_INTERVAL
+                    .saturating_sub(unsaved_since.elapsed())
+                    .as_millis()
+                    .min(c_int::MAX as u128) as c_int
+            },
+            None => -1,
+        }
+    }
+
+    fn autosave(&mut self) {
+        let Some(unsaved_since) = self.unsaved_since
+        else {
+            return;
+        };
+
+        if unsaved_since.elapsed() < AUTOSAVE_INTERVAL {
+            return;
+        }
+
+        if let Err(e) = self.save() {
+            // Try again after another interval instead of straight away
+            self.unsaved_since = Some(Instant::now());
+            self.status_message = Some(e);
+            self.redraw();
+        }
+    }
+
     fn update_words(&mut self) {
-        let grid = &self.puzzles[self.current_puzzle];
+        let grid = &self.puzzles[self.current_puzzle].grid;
 
         for (word_num, positions) in grid::WordPositions::new().enumerate() {
             let word = &mut self.words[word_num];
@@ -876,10 +2050,22 @@ impl Editor {
                 self.had_all_solutions = true;
                 self.redraw();
             },
+            SolutionEventKind::GridError(e) => {
+                self.grid_error = Some(e);
+                self.redraw();
+            },
+            SolutionEventKind::WordProblems(problems) => {
+                self.word_problems = Some(problems);
+                self.redraw();
+            },
             SolutionEventKind::SwapSolution(n_swaps) => {
                 self.shortest_swap_solution = Some(n_swaps);
                 self.redraw();
             },
+            SolutionEventKind::NoSwapSolution => {
+                self.no_swap_solution = true;
+                self.redraw();
+            },
         }
     }
 
@@ -888,38 +2074,139 @@ impl Editor {
         self.solutions.clear();
         self.had_all_solutions = false;
         self.shortest_swap_solution = None;
+        self.no_swap_solution = false;
+        self.word_problems = None;
+        self.grid_error = None;
 
-        let grid = self.puzzles[self.current_puzzle].clone();
+        let grid = self.puzzles[self.current_puzzle].grid.clone();
 
         self.solver_state.set_grid(self.grid_id, grid);
+
+        if self.fill_candidates.is_some() {
+            self.update_fill_candidates();
+        }
     }
 
     fn set_current_puzzle(&mut self, puzzle_num: usize) {
         if puzzle_num != self.current_puzzle {
             assert!(puzzle_num < self.puzzles.len());
             self.current_puzzle = puzzle_num;
+            self.load_current_puzzle();
+        }
+    }
 
-            // Set the non-empty letters as modified
-            self.added_letters = self.puzzles[self.current_puzzle]
-                .solution
-                .letters
-                .iter()
-                .enumerate()
-                .map(|(i, &ch)| {
-                    let modified = !grid::is_gap_position(i) &&
-                        !is_wildcard(ch);
-                    (modified as u32) << i
-                })
-                .fold(0, |a, b| a | b);
+    fn load_current_puzzle(&mut self) {
+        // Set the non-empty letters as modified
+        self.added_letters = self.puzzles[self.current_puzzle].grid
+            .solution
+            .letters
+            .iter()
+            .enumerate()
+            .map(|(i, &ch)| {
+                let modified = !grid::is_gap_position(i) &&
+                    !is_wildcard(ch);
+                (modified as u32) << i
+            })
+            .fold(0, |a, b| a | b);
 
-            self.update_words();
-            self.update_word_counts();
-            self.search_results = SearchResults::None;
-            self.send_grid();
-            self.redraw();
+        self.update_words();
+        self.update_word_counts();
+        self.search_results = SearchResults::None;
+        self.selected_result = None;
+        self.send_grid();
+        self.redraw();
+    }
+
+    fn undo_state(&self) -> UndoState {
+        UndoState {
+            grid: self.puzzles[self.current_puzzle].grid.clone(),
+            added_letters: self.added_letters,
         }
     }
 
+    // Remembers the current state of the puzzle so that the change
+    // that is about to be made can be undone
+    fn save_undo_state(&mut self) {
+        let state = self.undo_state();
+        let history = &mut self.histories[self.current_puzzle];
+
+        history.redo.clear();
+
+        if history.undo.last() == Some(&state) {
+            return;
+        }
+
+        if history.undo.len() >= MAX_UNDO_STEPS {
+            history.undo.remove(0);
+            history.truncated = true;
+        }
+
+        history.undo.push(state);
+    }
+
+    fn restore_undo_state(&mut self, state: UndoState) {
+        self.puzzles[self.current_puzzle].grid = state.grid;
+        self.added_letters = state.added_letters;
+        self.selected_position = None;
+        self.update_words();
+        self.send_grid();
+        self.redraw();
+    }
+
+    fn undo(&mut self) {
+        let current_state = self.undo_state();
+        let history = &mut self.histories[self.current_puzzle];
+
+        // Skip states that are the same as the current one, which can
+        // happen if the change didn’t end up changing anything
+        while history.undo.last() == Some(&current_state) {
+            history.undo.pop();
+        }
+
+        match history.undo.pop() {
+            Some(state) => {
+                history.redo.push(current_state);
+                self.restore_undo_state(state);
+            },
+            None => {
+                if history.created && !history.truncated {
+                    if self.puzzles.len() > 1 {
+                        self.remove_current_puzzle();
+                    } else {
+                        self.status_message =
+                            Some("Can’t remove the only puzzle".to_string());
+                        self.redraw();
+                    }
+                }
+            },
+        }
+    }
+
+    fn redo(&mut self) {
+        let current_state = self.undo_state();
+        let history = &mut self.histories[self.current_puzzle];
+
+        if let Some(state) = history.redo.pop() {
+            history.undo.push(current_state);
+            self.restore_undo_state(state);
+        }
+    }
+
+    fn remove_current_puzzle(&mut self) {
+        // The callers make sure that there is another puzzle to move
+        // to. Puzzles from the file can be removed with Ctrl+K so even
+        // a puzzle made with Ctrl+N can end up being the only one.
+        assert!(self.puzzles.len() > 1);
+
+        self.puzzles.remove(self.current_puzzle);
+        self.histories.remove(self.current_puzzle);
+
+        self.current_puzzle = self.current_puzzle
+            .min(self.puzzles.len() - 1);
+
+        self.load_current_puzzle();
+    }
+
     fn move_between_puzzles(&mut self, offset: isize) {
         let next_puzzle = self.current_puzzle.saturating_add_signed(offset)
             .min(self.puzzles.len() - 1);
@@ -950,12 +2237,16 @@ impl Editor {
         self.current_grid = GridChoice::Solution;
         self.edit_direction = EditDirection::Right;
 
-        self.puzzles.push(grid);
+        let date = self.puzzles.last().unwrap().date.add_days(1);
+
+        self.puzzles.push(ScheduledPuzzle { date, grid });
+        self.histories.push(History { created: true, ..Default::default() });
         self.set_current_puzzle(self.puzzles.len() - 1);
     }
 
     fn shuffle_puzzle(&mut self) {
-        let grid = &mut self.puzzles[self.current_puzzle];
+        self.save_undo_state();
+        let grid = &mut self.puzzles[self.current_puzzle].grid;
         shuffle_grid(&mut grid.puzzle);
         grid.update_square_states();
         self.send_grid();
@@ -964,20 +2255,23 @@ impl Editor {
 
     fn find_crosswords(&mut self) {
         let crosswords = crossword_solver::find_crosswords(
-            &self.puzzles[self.current_puzzle].solution,
+            &self.puzzles[self.current_puzzle].grid.solution,
             self.cursor_x,
             self.cursor_y,
             &self.dictionary,
         );
 
         self.search_results = SearchResults::Crosswords(crosswords);
+        self.search_cursor = (self.cursor_x, self.cursor_y);
+        self.selected_result = None;
 
         self.redraw();
     }
 
     fn generate_puzzle(&mut self) {
+        self.save_undo_state();
         let filtered_dictionary = self.take_filtered_dictionary();
-        let grid = &mut self.puzzles[self.current_puzzle];
+        let grid = &mut self.puzzles[self.current_puzzle].grid;
 
         let fixed_letters = (0..(WORD_LENGTH * WORD_LENGTH))
             .filter(|&pos| !grid::is_gap_position(pos))
@@ -992,7 +2286,8 @@ impl Editor {
 
         if let Some(generated_puzzle) = generate_puzzle::generate(
             &filtered_dictionary,
-            &fixed_letters
+            &fixed_letters,
+            &mut rand::thread_rng(),
         )
         {
             grid.solution = generated_puzzle;
@@ -1005,8 +2300,58 @@ impl Editor {
         self.filtered_dictionary = Some(filtered_dictionary);
     }
 
+    fn update_fill_candidates(&mut self) {
+        let filtered_dictionary = self.take_filtered_dictionary();
+
+        self.fill_candidates = Some(fill::candidates(
+            &self.puzzles[self.current_puzzle].grid.solution,
+            &filtered_dictionary,
+        ));
+
+        self.filtered_dictionary = Some(filtered_dictionary);
+    }
+
+    fn toggle_fill_assistant(&mut self) {
+        if self.fill_candidates.is_some() {
+            self.fill_candidates = None;
+        } else {
+            self.update_fill_candidates();
+        }
+
+        self.redraw();
+    }
+
+    fn fill_grid(&mut self) {
+        let filtered_dictionary = self.take_filtered_dictionary();
+
+        let filled = fill::fill(
+            &self.puzzles[self.current_puzzle].grid.solution,
+            &filtered_dictionary,
+            &mut rand::thread_rng(),
+        );
+
+        self.filtered_dictionary = Some(filtered_dictionary);
+
+        match filled {
+            Some(solution) => {
+                self.save_undo_state();
+                let grid = &mut self.puzzles[self.current_puzzle].grid;
+                grid.solution = solution;
+                grid.update_square_states();
+                self.update_words();
+                self.send_grid();
+            },
+            None => {
+                self.status_message =
+                    Some("No way to fill in the grid".to_string());
+            },
+        }
+
+        self.redraw();
+    }
+
     fn pattern_search(&mut self) {
-        let solution = &self.puzzles[self.current_puzzle].solution;
+        let solution = &self.puzzles[self.current_puzzle].grid.solution;
 
         let pattern = if self.cursor_y & 1 == 0 {
             solution.letters[
@@ -1025,14 +2370,94 @@ impl Editor {
 
         let words = word_search::search(&pattern, &self.dictionary);
 
-        self.search_results = SearchResults::Words(words);
+        self.search_results = SearchResults::Words(self.rank_words(words));
+        self.search_cursor = (self.cursor_x, self.cursor_y);
+        self.selected_result = None;
 
         self.redraw();
     }
 
+    // Returns the patterns of the slots that cross the slot under the
+    // cursor and that still have empty squares. Each pattern is given
+    // with the index in the crossing slot where it meets the slot
+    // under the cursor and the index in the slot under the cursor
+    // where it meets the crossing slot.
+    fn empty_crossings(&self) -> Vec<(String, usize, usize)> {
+        let solution = &self.puzzles[self.current_puzzle].grid.solution;
+        let is_row = self.cursor_y & 1 == 0;
+        let slot = if is_row { self.cursor_y } else { self.cursor_x } as usize;
+
+        (0..WORD_LENGTH).step_by(2).filter_map(|crossing| {
+            let pattern = (0..WORD_LENGTH).map(|i| {
+                let (x, y) = if is_row { (crossing, i) } else { (i, crossing) };
+                solution.letters[y * WORD_LENGTH + x]
+            }).collect::<String>();
+
+            pattern.chars()
+                .enumerate()
+                .any(|(i, ch)| i != slot && is_wildcard(ch))
+                .then_some((pattern, slot, crossing))
+        }).collect()
+    }
+
+    // Sorts the search results so that the best ones come first
+    fn rank_words(&self, words: Vec<String>) -> Vec<RankedWord> {
+        let crossings = self.empty_crossings()
+            .into_iter()
+            .map(|(pattern, crossing_index, word_index)| {
+                let counts = search_rank::crossing_letter_counts(
+                    &pattern,
+                    crossing_index,
+                    &self.dictionary,
+                );
+                (counts, word_index)
+            })
+            .collect::<Vec<_>>();
+
+        let current_puzzle_number = self.current_puzzle_number();
+        let mut upper_word = String::new();
+
+        let mut ranked_words = words.into_iter().map(|word| {
+            upper_word.clear();
+            upper_word.extend(word.chars().flat_map(char::to_uppercase));
+
+            let crossing_options = crossings.iter()
+                .map(|(counts, word_index)| {
+                    word.chars()
+                        .nth(*word_index)
+                        .and_then(|ch| counts.get(&ch).copied())
+                        .unwrap_or(0)
+                })
+                .min();
+
+            let factors = search_rank::Factors {
+                age: self.word_counter
+                    .counts(&upper_word)
+                    .map(|(_, _, last_use)| {
+                        last_use.abs_diff(current_puzzle_number)
+                    })
+                    .min(),
+                frequency: self.frequencies.as_ref()
+                    .map(|frequencies| frequencies.fraction(&word)),
+                crossing_options,
+            };
+
+            RankedWord {
+                score: search_rank::score(&factors),
+                word,
+            }
+        }).collect::<Vec<_>>();
+
+        search_rank::sort(&mut ranked_words);
+
+        ranked_words
+    }
+
     fn shuffle_search_results(&mut self) {
         let mut rng = rand::thread_rng();
 
+        self.selected_result = None;
+
         match self.search_results {
             SearchResults::None => (),
             SearchResults::Crosswords(ref mut crosswords) => {
@@ -1064,33 +2489,34 @@ impl Editor {
         let mut search_results = std::mem::take(&mut self.search_results);
         let mut upper_word = String::new();
 
-        let mut filter_words = |words: &mut Vec<String>| {
-            words.retain(|word| {
-                upper_word.clear();
-                upper_word.extend(
-                    word.chars()
-                        .map(char::to_uppercase)
-                        .flatten()
-                );
-                self.search_result_is_valid(&upper_word)
-            })
+        let mut is_valid = |word: &str| {
+            upper_word.clear();
+            upper_word.extend(
+                word.chars()
+                    .map(char::to_uppercase)
+                    .flatten()
+            );
+            self.search_result_is_valid(&upper_word)
         };
 
         match search_results {
             SearchResults::None => (),
             SearchResults::Crosswords(ref mut crosswords) => {
                 for crossword in crosswords.iter_mut() {
-                    filter_words(&mut crossword.a_words);
-                    filter_words(&mut crossword.b_words);
+                    crossword.a_words.retain(|word| is_valid(word));
+                    crossword.b_words.retain(|word| is_valid(word));
                 }
                 crosswords.retain(|cw| {
                     !cw.a_words.is_empty() && !cw.b_words.is_empty()
                 })
             },
-            SearchResults::Words(ref mut words) => filter_words(words),
+            SearchResults::Words(ref mut words) => {
+                words.retain(|word| is_valid(&word.word));
+            },
         }
 
         self.search_results = search_results;
+        self.selected_result = None;
 
         self.redraw()
     }
@@ -1098,13 +2524,27 @@ impl Editor {
     fn update_word_counts(&mut self) {
         self.word_counter.clear();
 
-        for (puzzle_num, puzzle) in self.puzzles.iter().enumerate() {
-            if puzzle_num == self.current_puzzle {
-                continue;
-            }
+        // The puzzles being edited replace any history puzzles for the
+        // same day
+        let dates = self.puzzles.iter()
+            .map(|puzzle| puzzle.date)
+            .collect::<HashSet<_>>();
+
+        let history = self.history.iter()
+            .filter(|puzzle| !dates.contains(&puzzle.date));
+
+        let puzzles = self.puzzles.iter()
+            .enumerate()
+            .filter(|&(index, _)| index != self.current_puzzle)
+            .map(|(_, puzzle)| puzzle);
+
+        for puzzle in puzzles.chain(history) {
+            let puzzle_num = puzzle.puzzle_number();
 
             for positions in grid::WordPositions::new() {
-                let word = positions.map(|pos| puzzle.solution.letters[pos]);
+                let word = positions.map(|pos| {
+                    puzzle.grid.solution.letters[pos]
+                });
                 self.word_counter.push(word, puzzle_num);
             }
         }
@@ -1137,12 +2577,8 @@ impl Editor {
     }
 }
 
-fn load_dictionary() -> Result<Arc<Dictionary>, ()> {
-    let filename = std::env::args_os()
-        .nth(1)
-        .unwrap_or("data/dictionary.bin".into());
-
-    match std::fs::read(&filename) {
+fn load_dictionary(filename: &OsStr) -> Result<Arc<Dictionary>, ()> {
+    match std::fs::read(filename) {
         Err(e) => {
             eprintln!(
                 "{}: {}",
@@ -1155,75 +2591,127 @@ fn load_dictionary() -> Result<Arc<Dictionary>, ()> {
     }
 }
 
-fn load_puzzles() -> Result<Vec<Grid>, ()> {
-    let filename = "puzzles.txt";
-    let mut puzzles = Vec::new();
-
-    let f = match std::fs::File::open(filename) {
-        Ok(f) => f,
+fn load_puzzles(filename: &Path) -> Result<Vec<ScheduledPuzzle>, ()> {
+    let data = match std::fs::read_to_string(filename) {
+        Ok(data) => data,
         Err(e) => {
             if e.kind() == std::io::ErrorKind::NotFound {
-                return Ok(vec![Grid::new()]);
+                return Ok(vec![ScheduledPuzzle {
+                    date: date::Date::first_puzzle(),
+                    grid: Grid::new(),
+                }]);
             } else {
-                eprintln!("{}: {}", filename, e);
+                eprintln!("{}: {}", filename.display(), e);
                 return Err(());
             }
         },
     };
 
-    for (line_num, line) in std::io::BufReader::new(f).lines().enumerate() {
-        let line = match line {
-            Ok(line) => line,
+    let puzzles = match schedule::parse(&data) {
+        Ok(puzzles) => puzzles,
+        Err(e) => {
+            eprintln!("{}: {}", filename.display(), e);
+            return Err(());
+        },
+    };
+
+    if puzzles.is_empty() {
+        eprintln!("{}: empty file", filename.display());
+        return Err(());
+    }
+
+    Ok(puzzles)
+}
+
+// Loads all of the history files. If more than one file has a puzzle
+// for the same day then the last one is used.
+fn load_history(filenames: &[OsString]) -> Result<Vec<ScheduledPuzzle>, ()> {
+    let mut puzzles = BTreeMap::new();
+
+    for filename in filenames.iter() {
+        let data = match std::fs::read_to_string(filename) {
+            Ok(data) => data,
             Err(e) => {
-                eprintln!("{}: {}", filename, e);
+                eprintln!("{}: {}", filename.to_string_lossy(), e);
                 return Err(());
             },
         };
 
-        let line = line.trim();
-
-        if line.is_empty() || line.starts_with('#') {
-            continue;
-        }
-
-        match line.parse::<Grid>() {
-            Ok(grid) => puzzles.push(grid),
+        match schedule::parse(&data) {
+            Ok(file_puzzles) => {
+                for puzzle in file_puzzles {
+                    puzzles.insert(puzzle.date, puzzle);
+                }
+            },
             Err(e) => {
-                eprintln!("{}:{}: {}", filename, line_num + 1, e);
+                eprintln!("{}: {}", filename.to_string_lossy(), e);
                 return Err(());
             },
         }
     }
 
-    if puzzles.is_empty() {
-        eprintln!("{}: empty file", filename);
-        return Err(());
-    }
+    Ok(puzzles.into_values().collect())
+}
 
-    Ok(puzzles)
+fn load_definitions(filename: &Path) -> Result<Definitions, ()> {
+    let data = match std::fs::read_to_string(filename) {
+        Ok(data) => data,
+        Err(e) => {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                return Ok(Definitions::new());
+            } else {
+                eprintln!("{}: {}", filename.display(), e);
+                return Err(());
+            }
+        },
+    };
+
+    definitions::parse(&data).map_err(|e| {
+        eprintln!("{}: {}", filename.display(), e);
+    })
 }
 
-fn save_puzzles(puzzles: &[Grid]) {
-    let f = match std::fs::File::create("puzzles.txt.tmp") {
-        Ok(f) => f,
-        Err(_) => return,
+fn load_frequencies(filename: &OsStr) -> Result<Frequencies, ()> {
+    let data = match std::fs::read_to_string(filename) {
+        Ok(data) => data,
+        Err(e) => {
+            eprintln!("{}: {}", filename.to_string_lossy(), e);
+            return Err(());
+        },
     };
 
-    let mut writer = std::io::BufWriter::new(f);
+    search_rank::parse_frequencies(&data).map_err(|e| {
+        eprintln!("{}: {}", filename.to_string_lossy(), e);
+    })
+}
 
-    for puzzle in puzzles.iter() {
-        if writeln!(writer, "{}", puzzle).is_err() {
-            return;
-        }
-    }
+// Appends an extension to the filename, for example to make
+// “puzzles.txt.tmp” from “puzzles.txt”
+fn add_extension(filename: &Path, extension: &str) -> PathBuf {
+    let mut filename = filename.as_os_str().to_owned();
+    filename.push(".");
+    filename.push(extension);
+    PathBuf::from(filename)
+}
 
-    if writer.flush().is_err() {
-        return;
-    }
+// Writes the file via a temporary file so that the old contents
+// aren’t lost if something goes wrong
+fn save_file(filename: &Path, contents: &str) -> Result<(), String> {
+    let tmp_filename = add_extension(filename, "tmp");
+
+    let write = || -> std::io::Result<()> {
+        let f = std::fs::File::create(&tmp_filename)?;
+        let mut writer = std::io::BufWriter::new(f);
 
-    std::mem::drop(writer);
+        writer.write_all(contents.as_bytes())?;
+        writer.flush()?;
 
-    let _ = std::fs::rename("puzzles.txt.tmp", "puzzles.txt");
+        std::mem::drop(writer);
+
+        std::fs::rename(&tmp_filename, filename)
+    };
+
+    write().map_err(|e| format!("{}: {}", filename.display(), e))
 }
 
 fn main_loop(
@@ -1249,7 +2737,7 @@ fn main_loop(
             libc::poll(
                 &mut pollfds as *mut libc::pollfd,
                 pollfds.len() as libc::nfds_t,
-                -1, // timeout
+                editor.autosave_timeout(),
             )
         };
 
@@ -1274,6 +2762,7 @@ fn main_loop(
         if pollfds[0].revents & libc::POLLIN != 0 {
             if let Some(key) = ncurses::get_wch() {
                 editor.handle_key(key);
+                editor.update_journal();
             }
         }
 
@@ -1292,6 +2781,8 @@ fn main_loop(
         for event in solver_thread.event_receiver.try_iter() {
             editor.handle_solution_event(event);
         }
+
+        editor.autosave();
     }
 }
 
@@ -1364,9 +2855,26 @@ impl SolverThread {
 
                 completed_grid_id = Some(grid_id);
 
-                let Ok(grid) = LetterGrid::from_grid(&grid)
-                else {
-                    continue;
+                if word_event_sender.send(SolutionEvent::new(
+                    grid_id,
+                    SolutionEventKind::WordProblems(
+                        rules::check_words(&dictionary, &grid),
+                    ),
+                )).is_err() {
+                    break;
+                }
+
+                let grid = match rules::hard_mode_letter_grid(&grid) {
+                    Ok(grid) => grid,
+                    Err(e) => {
+                        if word_event_sender.send(SolutionEvent::new(
+                            grid_id,
+                            SolutionEventKind::GridError(e),
+                        )).is_err() {
+                            break;
+                        }
+                        continue;
+                    },
                 };
 
                 let mut solver = GridSolver::new(
@@ -1411,14 +2919,24 @@ impl SolverThread {
                     swap_solver_state.later_task_is_pending(completed_grid_id)
                 };
 
-                if let Some(n_swaps) = minimum_swaps(&grid, should_cancel) {
-                    let event = SolutionEvent::new(
-                        grid_id,
-                        SolutionEventKind::SwapSolution(n_swaps),
-                    );
-                    if swap_event_sender.send(event).is_err() {
-                        break;
-                    }
+                let kind = match minimum_swaps(&grid, should_cancel) {
+                    Some(n_swaps) => SolutionEventKind::SwapSolution(n_swaps),
+                    None => {
+                        // The solver also gives up if a new grid is
+                        // sent, in which case there’s nothing to report
+                        if should_cancel() {
+                            continue;
+                        }
+
+                        SolutionEventKind::NoSwapSolution
+                    },
+                };
+
+                if swap_event_sender.send(SolutionEvent::new(
+                    grid_id,
+                    kind,
+                )).is_err() {
+                    break;
                 }
             }
         });
@@ -1466,12 +2984,60 @@ fn pipe() -> Result<(c_int, c_int), std::io::Error> {
 fn main() -> ExitCode {
     gettextrs::setlocale(gettextrs::LocaleCategory::LcAll, "");
 
-    let Ok(dictionary) = load_dictionary()
+    let cli = Cli::parse();
+
+    let puzzles_filename = PathBuf::from(
+        cli.puzzles.unwrap_or("puzzles.txt".into())
+    );
+
+    let files = Files {
+        journal: add_extension(&puzzles_filename, "journal"),
+        puzzles: puzzles_filename,
+        definitions: PathBuf::from(
+            cli.definitions.unwrap_or("definitions.txt".into())
+        ),
+    };
+
+    let Ok(dictionary) = load_dictionary(
+        &cli.dictionary.unwrap_or("data/dictionary.bin".into())
+    )
     else {
         return ExitCode::FAILURE;
     };
 
-    let Ok(puzzles) = load_puzzles()
+    let Ok(mut puzzles) = load_puzzles(&files.puzzles)
+    else {
+        return ExitCode::FAILURE;
+    };
+
+    let Ok(history) = load_history(&cli.history)
+    else {
+        return ExitCode::FAILURE;
+    };
+
+    let Ok(mut definitions) = load_definitions(&files.definitions)
+    else {
+        return ExitCode::FAILURE;
+    };
+
+    let frequencies = match cli.frequencies {
+        Some(ref filename) => match load_frequencies(filename) {
+            Ok(frequencies) => Some(frequencies),
+            Err(()) => return ExitCode::FAILURE,
+        },
+        None => None,
+    };
+
+    let Ok(n_recovered) = journal::replay_file(
+        &files.journal,
+        &mut puzzles,
+        &mut definitions,
+    )
+    else {
+        return ExitCode::FAILURE;
+    };
+
+    let Ok(journal) = Journal::open(&files.journal)
     else {
         return ExitCode::FAILURE;
     };
@@ -1490,6 +3056,13 @@ fn main() -> ExitCode {
     ncurses::keypad(ncurses::stdscr(), true);
     ncurses::start_color();
     ncurses::nodelay(ncurses::stdscr(), true);
+    ncurses::mousemask(
+        ncurses::BUTTON1_PRESSED as ncurses::mmask_t,
+        None,
+    );
+    // Report the presses straight away instead of waiting to see if
+    // they are part of a click
+    ncurses::mouseinterval(0);
 
     ncurses::init_pair(
         WRONG_LETTER_COLOR,
@@ -1517,19 +3090,44 @@ fn main() -> ExitCode {
         wakeup_write
     );
 
-    let mut editor = Editor::new(
+    let data = LoadedData {
         puzzles,
+        history,
+        definitions,
+        frequencies,
+    };
+
+    let mut editor = Editor::new(
+        data,
+        files,
+        journal,
         dictionary,
         Arc::clone(&solver_thread.solver_state),
         0,
         0,
     );
 
+    if n_recovered > 0 {
+        // Save the recovered changes straight away so that the
+        // journal can start again from the recovered state
+        editor.definitions_changed = true;
+
+        editor.status_message = Some(match editor.save() {
+            Ok(()) => format!(
+                "Recovered {} changes from a session that didn’t quit \
+                 normally",
+                n_recovered,
+            ),
+            Err(e) => e,
+        });
+    }
+
     editor.redraw();
 
     main_loop(&mut editor, &solver_thread, wakeup_read);
 
-    save_puzzles(&editor.puzzles);
+    let save_result = editor.save();
+    let journal_filename = editor.files.journal.clone();
 
     std::mem::drop(editor);
 
@@ -1542,5 +3140,18 @@ fn main() -> ExitCode {
 
     ncurses::endwin();
 
-    ExitCode::SUCCESS
+    match save_result {
+        Ok(()) => {
+            if let Err(e) = std::fs::remove_file(&journal_filename) {
+                eprintln!("{}: {}", journal_filename.display(), e);
+            }
+
+            ExitCode::SUCCESS
+        },
+        Err(e) => {
+            // Leave the journal so that the changes can be recovered
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        },
+    }
 }
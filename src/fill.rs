@@ -0,0 +1,334 @@
+// Vaflo – A word game in Esperanto
+// Copyright (C) 2025  Neil Roberts
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+// Helps to fill in a partially built solution grid. Each of the six
+// words in the grid is a slot and the candidates for a slot are the
+// words that match its pattern. The candidates are then whittled down
+// with arc consistency: a candidate is removed if the letter it would
+// put at an intersection isn’t used by any of the candidates of the
+// crossing slot. This is repeated until nothing changes.
+
+use std::collections::{HashSet, VecDeque};
+use super::dictionary::Dictionary;
+use super::grid::{self, SolutionGrid, N_WORDS};
+use super::wildcard;
+use super::word_search;
+use super::stem_word;
+use rand::prelude::*;
+
+// The candidates for each slot in the same order as
+// grid::WordPositions. The words are in lowercase like in the
+// dictionary.
+pub type Candidates = [Vec<String>; N_WORDS];
+
+fn slot_positions() -> [Vec<usize>; N_WORDS] {
+    let mut positions = grid::WordPositions::new();
+
+    std::array::from_fn(|_| positions.next().unwrap().collect())
+}
+
+// If the two slots cross then returns the index of the intersection
+// in each of them
+fn crossing(
+    positions: &[Vec<usize>; N_WORDS],
+    a: usize,
+    b: usize,
+) -> Option<(usize, usize)> {
+    if a == b {
+        return None;
+    }
+
+    positions[a].iter().enumerate().find_map(|(index_a, pos)| {
+        positions[b].iter()
+            .position(|other_pos| other_pos == pos)
+            .map(|index_b| (index_a, index_b))
+    })
+}
+
+fn letter_at(word: &str, index: usize) -> Option<char> {
+    word.chars().nth(index)
+}
+
+fn stem_of(word: &str) -> String {
+    let upper = word.to_uppercase();
+    stem_word::stem(&upper).to_string()
+}
+
+fn initial_candidates(
+    solution: &SolutionGrid,
+    dictionary: &Dictionary,
+    positions: &[Vec<usize>; N_WORDS],
+) -> Candidates {
+    std::array::from_fn(|slot| {
+        let pattern = positions[slot].iter()
+            .map(|&pos| solution.letters[pos])
+            .collect::<String>();
+
+        if pattern.chars().any(wildcard::is_wildcard) {
+            word_search::search(&pattern, dictionary)
+        } else {
+            // A slot that is already filled in keeps its word even
+            // if it isn’t in the dictionary so that the other slots
+            // still have to fit around it
+            vec![pattern.to_lowercase()]
+        }
+    })
+}
+
+// Removes the candidates of slot `a` that don’t have a matching
+// candidate in slot `b`. Returns true if anything was removed.
+fn revise(
+    candidates: &mut Candidates,
+    a: usize,
+    index_a: usize,
+    b: usize,
+    index_b: usize,
+) -> bool {
+    let letters = candidates[b].iter()
+        .filter_map(|word| letter_at(word, index_b))
+        .collect::<HashSet<char>>();
+
+    let old_len = candidates[a].len();
+
+    candidates[a].retain(|word| {
+        letter_at(word, index_a)
+            .map(|letter| letters.contains(&letter))
+            .unwrap_or(false)
+    });
+
+    candidates[a].len() != old_len
+}
+
+fn propagate(candidates: &mut Candidates, positions: &[Vec<usize>; N_WORDS]) {
+    let mut queue = VecDeque::new();
+
+    for a in 0..N_WORDS {
+        for b in 0..N_WORDS {
+            if crossing(positions, a, b).is_some() {
+                queue.push_back((a, b));
+            }
+        }
+    }
+
+    while let Some((a, b)) = queue.pop_front() {
+        let (index_a, index_b) = crossing(positions, a, b).unwrap();
+
+        if revise(candidates, a, index_a, b, index_b) {
+            for c in 0..N_WORDS {
+                if c != b
+                    && crossing(positions, c, a).is_some()
+                    && !queue.contains(&(c, a))
+                {
+                    queue.push_back((c, a));
+                }
+            }
+        }
+    }
+}
+
+// Works out the candidates for each slot of the grid
+pub fn candidates(
+    solution: &SolutionGrid,
+    dictionary: &Dictionary,
+) -> Candidates {
+    let positions = slot_positions();
+    let mut candidates = initial_candidates(solution, dictionary, &positions);
+
+    propagate(&mut candidates, &positions);
+
+    candidates
+}
+
+fn has_repeated_stem(candidates: &Candidates, slot: usize, word: &str) -> bool {
+    let stem = stem_of(word);
+
+    candidates.iter()
+        .enumerate()
+        .any(|(other_slot, other_candidates)| {
+            other_slot != slot
+                && other_candidates.len() == 1
+                && stem_of(&other_candidates[0]) == stem
+        })
+}
+
+fn search<R: Rng + ?Sized>(
+    candidates: Candidates,
+    positions: &[Vec<usize>; N_WORDS],
+    rng: &mut R,
+) -> Option<Candidates> {
+    if candidates.iter().any(|words| words.is_empty()) {
+        return None;
+    }
+
+    // Pick the undecided slot with the fewest candidates
+    let Some(slot) = (0..N_WORDS)
+        .filter(|&slot| candidates[slot].len() > 1)
+        .min_by_key(|&slot| candidates[slot].len())
+    else {
+        let stems = candidates.iter()
+            .map(|words| stem_of(&words[0]))
+            .collect::<HashSet<_>>();
+
+        return (stems.len() == N_WORDS).then_some(candidates);
+    };
+
+    let mut words = candidates[slot].clone();
+    words.shuffle(rng);
+
+    for word in words {
+        if has_repeated_stem(&candidates, slot, &word) {
+            continue;
+        }
+
+        let mut next_candidates = candidates.clone();
+        next_candidates[slot] = vec![word];
+        propagate(&mut next_candidates, positions);
+
+        if let Some(result) = search(next_candidates, positions, rng) {
+            return Some(result);
+        }
+    }
+
+    None
+}
+
+// Fills in all of the wildcards in the grid with words from the
+// dictionary so that no stem is used twice. Returns None if that
+// isn’t possible.
+pub fn fill<R: Rng + ?Sized>(
+    solution: &SolutionGrid,
+    dictionary: &Dictionary,
+    rng: &mut R,
+) -> Option<SolutionGrid> {
+    let positions = slot_positions();
+    let candidates = search(
+        candidates(solution, dictionary),
+        &positions,
+        rng,
+    )?;
+
+    let mut solution = solution.clone();
+
+    for (slot, words) in candidates.iter().enumerate() {
+        for (&pos, letter) in positions[slot].iter().zip(words[0].chars()) {
+            solution.letters[pos] = letter.to_uppercase().next()?;
+        }
+    }
+
+    Some(solution)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use super::super::dictionary::test_data;
+    use super::super::grid::Grid;
+
+    fn solution(letters: &str) -> SolutionGrid {
+        format!("{}abcdefhjklmnoprtuvwxy", letters)
+            .parse::<Grid>()
+            .unwrap()
+            .solution
+    }
+
+    fn letters(solution: &SolutionGrid) -> String {
+        solution.letters.iter()
+            .enumerate()
+            .map(|(pos, &ch)| if grid::is_gap_position(pos) { ' ' } else { ch })
+            .collect()
+    }
+
+    #[test]
+    fn crossings() {
+        let positions = slot_positions();
+
+        // Top row and left column
+        assert_eq!(crossing(&positions, 0, 1), Some((0, 0)));
+        // Middle row and right column
+        assert_eq!(crossing(&positions, 2, 5), Some((4, 2)));
+        // Two rows
+        assert_eq!(crossing(&positions, 0, 2), None);
+        assert_eq!(crossing(&positions, 3, 3), None);
+    }
+
+    #[test]
+    fn propagation() {
+        let dictionary = test_data::grid_words();
+
+        // With the top row filled in, arc consistency is enough to
+        // find the only solution
+        let slots = candidates(
+            &solution("ABCDE................"),
+            &dictionary,
+        );
+
+        assert_eq!(
+            slots.iter().map(|words| words.as_slice()).collect::<Vec<_>>(),
+            vec![
+                &["abcde"],
+                &["afkpu"],
+                &["klmno"],
+                &["chmrw"],
+                &["uvwxy"],
+                &["ejoty"],
+            ],
+        );
+
+        // Nothing can cross a row of Zs so the row itself is
+        // removed as well
+        let slots = candidates(
+            &solution("ZZZZZ................"),
+            &dictionary,
+        );
+
+        assert!(slots.iter().all(|words| words.is_empty()));
+    }
+
+    #[test]
+    fn filling() {
+        let dictionary = test_data::grid_words();
+
+        let filled = fill(
+            &solution("ABCDE................"),
+            &dictionary,
+            &mut rand::thread_rng(),
+        ).unwrap();
+
+        assert_eq!(
+            &letters(&filled),
+            "ABCDE\
+             F H J\
+             KLMNO\
+             P R T\
+             UVWXY",
+        );
+
+        // An empty grid can be filled either way round
+        let filled = fill(
+            &solution("....................."),
+            &dictionary,
+            &mut rand::thread_rng(),
+        ).unwrap();
+
+        assert!(filled.letters[1] == 'B' || filled.letters[1] == 'F');
+
+        assert!(fill(
+            &solution("ZZZZZ................"),
+            &dictionary,
+            &mut rand::thread_rng(),
+        ).is_none());
+    }
+}
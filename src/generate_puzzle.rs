@@ -18,14 +18,17 @@ use super::dictionary::{Dictionary, Node};
 use super::grid::{WORD_LENGTH, N_WORDS_ON_AXIS, SolutionGrid};
 use rand::prelude::*;
 
-fn list_siblings<'a>(first_node: Node<'a>) -> Vec<Node<'a>> {
+fn list_siblings<'a, R: Rng + ?Sized>(
+    first_node: Node<'a>,
+    rng: &mut R,
+) -> Vec<Node<'a>> {
     let mut siblings = vec![first_node];
 
     while let Some(next_sibling) = siblings.last().unwrap().next_sibling() {
         siblings.push(next_sibling);
     }
 
-    siblings.shuffle(&mut rand::thread_rng());
+    siblings.shuffle(rng);
 
     siblings
 }
@@ -66,9 +69,10 @@ fn find_sibling<'a>(
     None
 }
 
-pub fn generate(
+pub fn generate<R: Rng + ?Sized>(
     dictionary: &Dictionary,
     fixed_letters: &[Option<char>],
+    rng: &mut R,
 ) -> Option<SolutionGrid> {
     let Some(first_node) = dictionary.first_node()
     else {
@@ -80,7 +84,7 @@ pub fn generate(
             first_node.clone()
         });
     let mut vertical_words = horizontal_words.clone();
-    let mut stack = vec![list_siblings(first_node.clone())];
+    let mut stack = vec![list_siblings(first_node.clone(), rng)];
 
     while let Some(mut siblings) = stack.pop() {
         let Some(node) = siblings.pop()
@@ -157,7 +161,7 @@ pub fn generate(
             let next_group_pos = next_pos % (WORD_LENGTH + N_WORDS_ON_AXIS);
 
             if next_group_pos == 0 {
-                stack.push(list_siblings(first_node.clone()));
+                stack.push(list_siblings(first_node.clone(), rng));
             } else {
                 let parent = if next_group_pos < WORD_LENGTH {
                     &node
@@ -169,7 +173,7 @@ pub fn generate(
                 };
 
                 if let Some(first_child) = parent.first_child() {
-                    stack.push(list_siblings(first_child));
+                    stack.push(list_siblings(first_child, rng));
                 }
             }
         }
@@ -181,37 +185,15 @@ pub fn generate(
 #[cfg(test)]
 mod test {
     use super::*;
-
-    fn make_test_dictionary() -> Dictionary {
-        // Dictionary that contains the words “abcde”, “fghij”,
-        // “klmno”, “pqrst”, “uvwxy”, “afkpu”, “bglqv”, “chmrw”,
-        // “dinsx” and “ejoty”
-        static DICTIONARY_BYTES: [u8; 150] = [
-            0x00, 0x01, 0x2a, 0x01, 0x16, b'a', 0x01, 0x1f, b'b',
-            0x01, 0x1f, b'c', 0x01, 0x1f, b'd', 0x01, 0x1f, b'e',
-            0x01, 0x10, b'f', 0x01, 0x1c, b'k', 0x04, 0x1c, b'p',
-            0x04, 0x1f, b'b', 0x00, 0x19, b'u', 0x00, 0x1f, b'f',
-            0x00, 0x19, b'g', 0x00, 0x1c, b'g', 0x00, 0x1f, b'h',
-            0x00, 0x1f, b'i', 0x00, 0x1f, b'j', 0x00, 0x13, b'l',
-            0x00, 0x1c, b'q', 0x00, 0x1c, b'v', 0x00, 0x1c, b'c',
-            0x00, 0x1c, b'h', 0x00, 0x1f, b'k', 0x00, 0x1f, b'l',
-            0x00, 0x16, b'm', 0x00, 0x1c, b'm', 0x00, 0x1f, b'n',
-            0x00, 0x1f, b'o', 0x00, 0x16, b'r', 0x00, 0x1c, b'w',
-            0x00, 0x1c, b'd', 0x00, 0x1c, b'i', 0x00, 0x1c, b'n',
-            0x00, 0x1f, b'p', 0x00, 0x1f, b'q', 0x00, 0x1f, b'r',
-            0x00, 0x13, b's', 0x00, 0x1c, b's', 0x00, 0x1c, b't',
-            0x00, 0x19, b'x', 0x00, 0x19, b'e', 0x00, 0x16, b'j',
-            0x00, 0x13, b'o', 0x00, 0x10, b't', 0x00, 0x0d, b'u',
-            0x00, 0x0a, b'v', 0x00, 0x07, b'w', 0x00, 0x04, b'x',
-            0x00, 0x01, b'y', 0x00, 0x00, 0x00,
-        ];
-
-        Dictionary::new(Box::new(DICTIONARY_BYTES.clone()))
-    }
+    use super::super::dictionary::test_data;
 
     #[test]
     fn test_generate() {
-        let grid = generate(&make_test_dictionary(), &[]).unwrap();
+        let grid = generate(
+            &test_data::grid_words(),
+            &[],
+            &mut rand::thread_rng(),
+        ).unwrap();
 
         // There are two possible solutions and it will randomly pick
         // one of them
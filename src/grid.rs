@@ -25,7 +25,7 @@ pub const N_LETTERS: usize =
 
 use std::fmt;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct SolutionGrid {
     // The solution contains the actual letters. The grid is stored as
     // an array including positions for the gaps to make it easier to
@@ -40,20 +40,20 @@ pub enum PuzzleSquareState {
     Wrong,
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct PuzzleSquare {
     pub position: usize,
     pub state: PuzzleSquareState,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct PuzzleGrid {
     // The puzzle is stored is indices into the solution grid so that
     // changing a letter will change it in both grids
     pub squares: [PuzzleSquare; WORD_LENGTH * WORD_LENGTH]
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Grid {
     pub solution: SolutionGrid,
     pub puzzle: PuzzleGrid,
@@ -167,7 +167,7 @@ impl Grid {
         }
     }
 
-    pub fn update_square_states(&mut self) {
+    fn update_correct_square_states(&mut self) {
         for (i, square) in self.puzzle.squares.iter_mut().enumerate() {
             if self.solution.letters[i]
                 == self.solution.letters[square.position]
@@ -177,11 +177,22 @@ impl Grid {
                 square.state = PuzzleSquareState::Wrong;
             }
         }
+    }
+
+    pub fn update_square_states(&mut self) {
+        self.update_correct_square_states();
 
         for word in WordPositions::new() {
             self.update_square_letters_for_word(word);
         }
     }
+
+    // Like update_square_states but for hard mode where the letters
+    // in the wrong position aren’t marked so every square is either
+    // Correct or Wrong.
+    pub fn update_square_states_hard(&mut self) {
+        self.update_correct_square_states();
+    }
 }
 
 impl fmt::Display for Grid {
@@ -496,6 +507,38 @@ mod test {
         assert_eq!(squares[24].state, PuzzleSquareState::WrongPosition);
     }
 
+    #[test]
+    fn hard_square_states() {
+        let mut grid = "KULPOEIKMANĜUIDPOMAĜI\
+                        jlmorpaknbchdftwuyexv"
+            .parse::<Grid>().unwrap();
+
+        grid.update_square_states_hard();
+
+        let row = &grid.puzzle.squares[
+            WORD_LENGTH * (WORD_LENGTH - 1)..WORD_LENGTH * WORD_LENGTH
+        ];
+
+        assert_eq!(row[0].state, PuzzleSquareState::Correct);
+        assert_eq!(row[1].state, PuzzleSquareState::Wrong);
+        assert_eq!(row[2].state, PuzzleSquareState::Wrong);
+        assert_eq!(row[3].state, PuzzleSquareState::Correct);
+        assert_eq!(row[4].state, PuzzleSquareState::Wrong);
+
+        assert!(grid.puzzle.squares.iter().all(|square| {
+            square.state != PuzzleSquareState::WrongPosition
+        }));
+
+        // Switching back to the normal mode should bring back the
+        // yellow squares
+        grid.update_square_states();
+
+        assert_eq!(
+            grid.puzzle.squares[WORD_LENGTH * (WORD_LENGTH - 1) + 1].state,
+            PuzzleSquareState::WrongPosition,
+        );
+    }
+
     #[test]
     fn solved() {
         let grid = "MORSAUUKROLASDOOURSOJ\
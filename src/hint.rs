@@ -0,0 +1,139 @@
+// Vaflo – A word game in Esperanto
+// Copyright (C) 2025  Neil Roberts
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use super::grid::Grid;
+use super::swap_solver;
+
+// Returns a swap that takes the puzzle one step closer to being
+// solved in the minimum number of swaps, or None if the puzzle is
+// already solved. The full solver can take several seconds on a
+// freshly scrambled puzzle, so instead this tries the swaps that put
+// at least one letter in the right place and picks the first one that
+// lowers the minimum number of swaps. There is always such a swap
+// because each cycle of misplaced letters can be shortened by moving
+// one letter to its place.
+pub fn next_swap(grid: &Grid) -> Option<(usize, usize)> {
+    let target = &grid.solution.letters;
+    let mut puzzle = grid.puzzle
+        .squares
+        .iter()
+        .map(|square| target[square.position])
+        .collect::<Vec<char>>();
+
+    let n_swaps = swap_solver::minimum_swaps(&puzzle, target)?;
+
+    if n_swaps == 0 {
+        return None;
+    }
+
+    let is_wrong = |puzzle: &[char], pos: usize| puzzle[pos] != target[pos];
+
+    for a in 0..puzzle.len() {
+        if !is_wrong(&puzzle, a) {
+            continue;
+        }
+
+        for b in a + 1..puzzle.len() {
+            if !is_wrong(&puzzle, b)
+                || (puzzle[a] != target[b] && puzzle[b] != target[a])
+            {
+                continue;
+            }
+
+            puzzle.swap(a, b);
+            let next_n_swaps = swap_solver::minimum_swaps(&puzzle, target);
+            puzzle.swap(a, b);
+
+            if next_n_swaps == Some(n_swaps - 1) {
+                return Some((a, b));
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use super::super::grid::PuzzleSquareState;
+
+    fn minimum_swaps(grid: &Grid) -> usize {
+        let puzzle = grid.puzzle
+            .squares
+            .iter()
+            .map(|square| grid.solution.letters[square.position])
+            .collect::<Vec<char>>();
+
+        swap_solver::minimum_swaps(&puzzle, &grid.solution.letters).unwrap()
+    }
+
+    fn follow_hints(mut grid: Grid, expected_n_swaps: usize) {
+        grid.update_square_states();
+
+        let mut n_swaps = minimum_swaps(&grid);
+        assert_eq!(n_swaps, expected_n_swaps);
+
+        while let Some((a, b)) = next_swap(&grid) {
+            // The hint should never involve a letter that is already
+            // in the right place
+            assert_ne!(grid.puzzle.squares[a].state, PuzzleSquareState::Correct);
+            assert_ne!(grid.puzzle.squares[b].state, PuzzleSquareState::Correct);
+
+            grid.puzzle.squares.swap(a, b);
+            grid.update_square_states();
+
+            let next_n_swaps = minimum_swaps(&grid);
+            assert_eq!(next_n_swaps + 1, n_swaps);
+            n_swaps = next_n_swaps;
+        }
+
+        assert_eq!(n_swaps, 0);
+        assert!(grid.puzzle.is_solved());
+    }
+
+    #[test]
+    fn few_swaps() {
+        let mut grid = "MORSAUUKROLASDOOURSOJ\
+                        arcdnhfjvlmewpxbukoty"
+            .parse::<Grid>().unwrap();
+
+        for (a, b) in [(0, 4), (10, 12), (20, 24)] {
+            grid.puzzle.squares.swap(a, b);
+        }
+
+        follow_hints(grid, 3);
+    }
+
+    #[test]
+    fn scrambled_puzzle() {
+        // A real puzzle from puzzles.txt that the full solver takes
+        // several seconds to solve
+        let grid = "FRIDIRRRACIDOTTJABIONmdcfralouhpyjbexkvntw"
+            .parse::<Grid>().unwrap();
+
+        follow_hints(grid, 10);
+    }
+
+    #[test]
+    fn solved() {
+        let grid = "MORSAUUKROLASDOOURSOJ\
+                    arcdnhfjvlmewpxbukoty"
+            .parse::<Grid>().unwrap();
+
+        assert!(next_swap(&grid).is_none());
+    }
+}
@@ -0,0 +1,319 @@
+// Vaflo – A word game in Esperanto
+// Copyright (C) 2025  Neil Roberts
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+// The journal is an append-only file of the changes made in the
+// editor since the files were last saved so that they can be
+// recovered if the editor doesn’t quit normally. Each line is one of:
+//   P DATE GRID       the puzzle for DATE is now GRID
+//   R DATE            the puzzle for DATE was removed
+//   D WORD DEFINITION the definition of WORD is now DEFINITION
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use super::grid::Grid;
+use super::date::Date;
+use super::schedule::ScheduledPuzzle;
+use super::definitions::Definitions;
+
+pub struct Journal {
+    filename: PathBuf,
+    file: std::fs::File,
+}
+
+impl Journal {
+    pub fn open(filename: &Path) -> Result<Journal, ()> {
+        match std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(filename)
+        {
+            Ok(file) => Ok(Journal { filename: filename.to_owned(), file }),
+            Err(e) => {
+                eprintln!("{}: {}", filename.display(), e);
+                Err(())
+            },
+        }
+    }
+
+    pub fn add_line(&mut self, line: &str) -> Result<(), String> {
+        // Write the line in one go so that a crash can at most leave
+        // a partial last line
+        self.file.write_all(format!("{}\n", line).as_bytes())
+            .map_err(|e| format!("{}: {}", self.filename.display(), e))
+    }
+
+    pub fn clear(&mut self) {
+        let _ = self.file.set_len(0);
+    }
+}
+
+fn apply_line(
+    puzzles: &mut Vec<ScheduledPuzzle>,
+    definitions: &mut Definitions,
+    line: &str,
+) -> Option<()> {
+    let (command, rest) = line.split_once(' ')?;
+
+    match command {
+        "P" => {
+            let (date, grid) = rest.split_once(' ')?;
+            let date = date.parse::<Date>().ok()?;
+            let grid = grid.parse::<Grid>().ok()?;
+
+            date.puzzle_number()?;
+
+            match puzzles.iter().position(|puzzle| puzzle.date >= date) {
+                Some(index) if puzzles[index].date == date => {
+                    puzzles[index].grid = grid;
+                },
+                Some(index) => {
+                    puzzles.insert(index, ScheduledPuzzle { date, grid });
+                },
+                None => puzzles.push(ScheduledPuzzle { date, grid }),
+            }
+        },
+        "R" => {
+            let date = rest.parse::<Date>().ok()?;
+            let index = puzzles.iter().position(|puzzle| puzzle.date == date)?;
+
+            // The editor always needs at least one puzzle
+            if puzzles.len() <= 1 {
+                return None;
+            }
+
+            puzzles.remove(index);
+        },
+        "D" => {
+            let (word, definition) = rest.split_once(' ')
+                .unwrap_or((rest, ""));
+            definitions.set(word, definition);
+        },
+        _ => return None,
+    }
+
+    Some(())
+}
+
+// Applies the changes in the contents of a journal. Lines that can’t
+// be applied are skipped. This can happen if the editor crashed in
+// the middle of writing the last line. Returns the number of changes
+// applied.
+pub fn replay(
+    data: &str,
+    puzzles: &mut Vec<ScheduledPuzzle>,
+    definitions: &mut Definitions,
+) -> usize {
+    data.lines()
+        .filter(|line| apply_line(puzzles, definitions, line).is_some())
+        .count()
+}
+
+// Applies the changes in the journal left behind by a session that
+// didn’t quit normally. Returns the number of changes applied.
+pub fn replay_file(
+    filename: &Path,
+    puzzles: &mut Vec<ScheduledPuzzle>,
+    definitions: &mut Definitions,
+) -> Result<usize, ()> {
+    match std::fs::read_to_string(filename) {
+        Ok(data) => Ok(replay(&data, puzzles, definitions)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(0),
+        Err(e) => {
+            eprintln!("{}: {}", filename.display(), e);
+            Err(())
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use super::super::schedule;
+
+    static GRID_A: &str = "BATOJRAAINTERDUDIRILOakdyetwfcnrlpjbmuohxv";
+    static GRID_B: &str = "MORSAUUKROLASDOOURSOJarcdnhfjvlmewpxbukoty";
+
+    fn puzzles(data: &str) -> Vec<ScheduledPuzzle> {
+        schedule::parse(data).unwrap()
+    }
+
+    fn dates(puzzles: &[ScheduledPuzzle]) -> Vec<String> {
+        puzzles.iter().map(|puzzle| puzzle.date.to_string()).collect()
+    }
+
+    #[test]
+    fn replace_puzzle() {
+        let mut puzzles = puzzles(&format!(
+            "2024-01-01 {}\n2024-01-02 {}\n",
+            GRID_A,
+            GRID_A,
+        ));
+        let mut definitions = Definitions::new();
+
+        assert_eq!(
+            replay(
+                &format!("P 2024-01-02 {}\n", GRID_B),
+                &mut puzzles,
+                &mut definitions,
+            ),
+            1,
+        );
+
+        assert_eq!(dates(&puzzles), &["2024-01-01", "2024-01-02"]);
+        assert_eq!(&puzzles[0].grid.to_string(), GRID_A);
+        assert_eq!(&puzzles[1].grid.to_string(), GRID_B);
+    }
+
+    #[test]
+    fn insert_puzzle() {
+        let mut puzzles = puzzles(&format!(
+            "2024-01-02 {}\n2024-01-04 {}\n",
+            GRID_A,
+            GRID_A,
+        ));
+        let mut definitions = Definitions::new();
+
+        assert_eq!(
+            replay(
+                &format!(
+                    "P 2024-01-03 {}\n\
+                     P 2024-01-01 {}\n\
+                     P 2024-01-05 {}\n",
+                    GRID_B,
+                    GRID_B,
+                    GRID_B,
+                ),
+                &mut puzzles,
+                &mut definitions,
+            ),
+            3,
+        );
+
+        assert_eq!(
+            dates(&puzzles),
+            &[
+                "2024-01-01",
+                "2024-01-02",
+                "2024-01-03",
+                "2024-01-04",
+                "2024-01-05",
+            ],
+        );
+        assert_eq!(&puzzles[2].grid.to_string(), GRID_B);
+        assert_eq!(&puzzles[3].grid.to_string(), GRID_A);
+    }
+
+    #[test]
+    fn remove_puzzle() {
+        let mut puzzles = puzzles(&format!(
+            "2024-01-01 {}\n2024-01-02 {}\n",
+            GRID_A,
+            GRID_B,
+        ));
+        let mut definitions = Definitions::new();
+
+        // There is no puzzle for the first date so it is skipped
+        assert_eq!(
+            replay(
+                "R 2024-02-01\n\
+                 R 2024-01-01\n",
+                &mut puzzles,
+                &mut definitions,
+            ),
+            1,
+        );
+
+        assert_eq!(dates(&puzzles), &["2024-01-02"]);
+
+        // The only puzzle can’t be removed
+        assert_eq!(
+            replay("R 2024-01-02\n", &mut puzzles, &mut definitions),
+            0,
+        );
+
+        assert_eq!(dates(&puzzles), &["2024-01-02"]);
+    }
+
+    #[test]
+    fn definitions() {
+        let mut puzzles = puzzles(GRID_A);
+        let mut definitions = Definitions::new();
+
+        assert_eq!(
+            replay(
+                "D BATOJ batalo\n\
+                 D TERDU\n\
+                 D BATOJ \n\
+                 D IRILO ilo por iri\n",
+                &mut puzzles,
+                &mut definitions,
+            ),
+            4,
+        );
+
+        // An empty definition removes it
+        assert_eq!(definitions.get("BATOJ"), None);
+        assert_eq!(definitions.get("TERDU"), None);
+        assert_eq!(definitions.get("IRILO"), Some("ilo por iri"));
+    }
+
+    #[test]
+    fn truncated_line() {
+        let mut puzzles = puzzles(&format!("2024-01-01 {}\n", GRID_A));
+        let mut definitions = Definitions::new();
+
+        assert_eq!(
+            replay(
+                &format!(
+                    "P 2024-01-02 {}\n\
+                     P 2024-01-03 {}",
+                    GRID_B,
+                    &GRID_B[..30],
+                ),
+                &mut puzzles,
+                &mut definitions,
+            ),
+            1,
+        );
+
+        assert_eq!(dates(&puzzles), &["2024-01-01", "2024-01-02"]);
+    }
+
+    #[test]
+    fn invalid_lines() {
+        let mut puzzles = puzzles(GRID_A);
+        let mut definitions = Definitions::new();
+
+        assert_eq!(
+            replay(
+                &format!(
+                    "\n\
+                     X 2024-01-02\n\
+                     P\n\
+                     P 2024-13-01 {}\n\
+                     P 2020-01-01 {}\n",
+                    GRID_B,
+                    GRID_B,
+                ),
+                &mut puzzles,
+                &mut definitions,
+            ),
+            0,
+        );
+
+        assert_eq!(puzzles.len(), 1);
+    }
+}
@@ -22,3 +22,41 @@ mod grid;
 mod save_state;
 #[cfg(any(target_arch = "wasm32", test))]
 mod stars;
+#[cfg(any(target_arch = "wasm32", test))]
+mod settings;
+#[cfg(any(target_arch = "wasm32", test))]
+mod pairs;
+#[cfg(any(target_arch = "wasm32", test))]
+mod swap_solver;
+#[cfg(any(target_arch = "wasm32", test))]
+mod hint;
+#[cfg(any(target_arch = "wasm32", test))]
+mod sync;
+#[cfg(any(target_arch = "wasm32", test))]
+mod date;
+#[cfg(any(target_arch = "wasm32", test))]
+mod share_template;
+#[cfg(test)]
+mod schedule;
+#[cfg(any(target_arch = "wasm32", test))]
+mod definitions;
+#[cfg(any(target_arch = "wasm32", test))]
+mod daily_puzzle;
+#[cfg(any(target_arch = "wasm32", test))]
+mod dictionary;
+#[cfg(any(target_arch = "wasm32", test))]
+mod generate_puzzle;
+#[cfg(any(target_arch = "wasm32", test))]
+mod letter_grid;
+#[cfg(any(target_arch = "wasm32", test))]
+mod word_grid;
+#[cfg(any(target_arch = "wasm32", test))]
+mod word_solver;
+#[cfg(any(target_arch = "wasm32", test))]
+mod permute;
+#[cfg(any(target_arch = "wasm32", test))]
+mod grid_solver;
+#[cfg(any(target_arch = "wasm32", test))]
+mod practice;
+#[cfg(any(target_arch = "wasm32", test))]
+mod puzzle_check;
@@ -0,0 +1,183 @@
+// Vaflo – A word game in Esperanto
+// Copyright (C) 2025  Neil Roberts
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+// Makes random puzzles for the practice mode. These are made the
+// same way as the puzzles in the editor but without a human checking
+// that the words are nice.
+
+use super::dictionary::Dictionary;
+use super::grid::{self, Grid, WORD_LENGTH};
+use super::generate_puzzle;
+use super::swap_solver;
+use super::letter_grid::LetterGrid;
+use super::word_grid::WordGrid;
+use super::grid_solver::GridSolver;
+use super::stars::{MAXIMUM_SWAPS, MAXIMUM_STARS};
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+// Number of swaps needed to solve the puzzle, the same as for the
+// daily puzzles
+const N_SWAPS: usize = (MAXIMUM_SWAPS - MAXIMUM_STARS) as usize;
+// Range of the number of letters that are moved by the shuffle. The
+// daily puzzles usually have between four and eight letters in the
+// right place at the start. Leaving some letters in place also makes
+// it much quicker to check that there is only one solution.
+const MIN_MOVED_LETTERS: usize = 13;
+const MAX_MOVED_LETTERS: usize = 17;
+// Number of times to try shuffling a solution before giving up and
+// generating a different one
+const N_SHUFFLE_ATTEMPTS: usize = 8;
+// Number of solutions to generate before giving up altogether
+const N_SOLUTION_ATTEMPTS: usize = 32;
+
+// Resets the puzzle and then moves some of the letters around in
+// cycles so that it would need N_SWAPS swaps to solve it if all of
+// the letters were different
+fn shuffle<R: Rng + ?Sized>(grid: &mut Grid, rng: &mut R) {
+    grid.puzzle.reset();
+
+    let n_moved = rng.gen_range(MIN_MOVED_LETTERS..=MAX_MOVED_LETTERS);
+    // A cycle of n letters needs n-1 swaps
+    let n_cycles = n_moved - N_SWAPS;
+
+    let mut positions = (0..WORD_LENGTH * WORD_LENGTH)
+        .filter(|&pos| !grid::is_gap_position(pos))
+        .collect::<Vec<_>>();
+    positions.shuffle(rng);
+
+    // Every cycle needs at least two letters. The rest are shared
+    // out randomly.
+    let mut cycle_lengths = vec![2; n_cycles];
+
+    for _ in n_cycles * 2..n_moved {
+        cycle_lengths[rng.gen_range(0..n_cycles)] += 1;
+    }
+
+    let mut positions = &positions[..];
+
+    for length in cycle_lengths {
+        let (cycle, rest) = positions.split_at(length);
+
+        for pair in cycle.windows(2) {
+            grid.puzzle.squares.swap(pair[0], pair[1]);
+        }
+
+        positions = rest;
+    }
+
+    grid.update_square_states();
+}
+
+fn minimum_swaps(grid: &Grid) -> Option<usize> {
+    let puzzle = grid.puzzle
+        .squares
+        .iter()
+        .map(|square| grid.solution.letters[square.position])
+        .collect::<Vec<char>>();
+
+    swap_solver::minimum_swaps(&puzzle, &grid.solution.letters)
+}
+
+// Checks that the only grid that can be made by swapping the
+// letters is the solution
+fn has_one_solution(grid: &Grid, dictionary: &Dictionary) -> bool {
+    let Ok(letter_grid) = LetterGrid::from_grid(grid)
+    else {
+        return false;
+    };
+
+    let mut solver = GridSolver::new(WordGrid::new(&letter_grid), dictionary);
+
+    solver.next().is_some() && solver.next().is_none()
+}
+
+// Generates a random puzzle that needs the same number of swaps as
+// the daily puzzles and that only has one solution. Returns None if
+// no such puzzle was found after several attempts.
+pub fn generate<R: Rng + ?Sized>(
+    dictionary: &Dictionary,
+    rng: &mut R,
+) -> Option<Grid> {
+    for _ in 0..N_SOLUTION_ATTEMPTS {
+        let mut grid = Grid::new();
+
+        grid.solution = generate_puzzle::generate(dictionary, &[], rng)?;
+
+        for _ in 0..N_SHUFFLE_ATTEMPTS {
+            shuffle(&mut grid, rng);
+
+            // Repeated letters can make it possible to solve the
+            // puzzle in fewer swaps
+            if minimum_swaps(&grid) == Some(N_SWAPS)
+                && has_one_solution(&grid, dictionary)
+            {
+                return Some(grid);
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn load_dictionary() -> Dictionary {
+        let data = std::fs::read("data/dictionary.bin").unwrap();
+        Dictionary::new(data.into_boxed_slice())
+    }
+
+    #[test]
+    fn shuffled_letters() {
+        let mut rng = rand::thread_rng();
+        // Grid where all of the letters are different
+        let mut grid = "ABCDEFHJKLMNOPRTUVWXY\
+                        abcdefhjklmnoprtuvwxy"
+            .parse::<Grid>()
+            .unwrap();
+
+        for _ in 0..100 {
+            shuffle(&mut grid, &mut rng);
+
+            let n_moved = grid.puzzle.squares.iter()
+                .enumerate()
+                .filter(|&(pos, square)| square.position != pos)
+                .count();
+
+            assert!(n_moved >= MIN_MOVED_LETTERS);
+            assert!(n_moved <= MAX_MOVED_LETTERS);
+            assert_eq!(minimum_swaps(&grid), Some(N_SWAPS));
+        }
+    }
+
+    #[test]
+    fn generated_puzzle() {
+        let dictionary = load_dictionary();
+        let grid = generate(&dictionary, &mut rand::thread_rng()).unwrap();
+
+        assert!(!grid.puzzle.is_solved());
+        assert_eq!(minimum_swaps(&grid), Some(N_SWAPS));
+        assert!(has_one_solution(&grid, &dictionary));
+
+        for word in grid::WordPositions::new().map(|positions| {
+            positions.map(|pos| grid.solution.letters[pos])
+        }) {
+            assert!(dictionary.contains(word));
+        }
+    }
+}
@@ -0,0 +1,199 @@
+// Vaflo – A word game in Esperanto
+// Copyright (C) 2025  Neil Roberts
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+// Quick checks that the game can do on a puzzle before letting the
+// player start it. These don’t need the dictionary so they can’t
+// catch everything that check-puzzles does, but they catch the
+// mistakes that would make the game unplayable.
+
+use std::fmt;
+use std::collections::HashSet;
+use super::grid::{self, Grid, WORD_LENGTH};
+use super::swap_solver;
+use super::stars::{MAXIMUM_SWAPS, MAXIMUM_STARS};
+
+// The most swaps that a puzzle can need to be solved. The stars are
+// counted assuming this many so if it needs more then the player can
+// never get all of the stars.
+const MAX_MINIMUM_SWAPS: usize = (MAXIMUM_SWAPS - MAXIMUM_STARS) as usize;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Problem {
+    InvalidPermutation,
+    DuplicateWord(String),
+    UnbalancedLetters,
+    MinimumSwaps(usize),
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Problem::InvalidPermutation => {
+                write!(f, "the puzzle positions aren’t a permutation")
+            },
+            Problem::DuplicateWord(word) => {
+                write!(f, "“{}” appears more than once", word)
+            },
+            Problem::UnbalancedLetters => {
+                write!(f, "the puzzle letters don’t match the solution")
+            },
+            Problem::MinimumSwaps(swaps) => {
+                write!(f, "minimum number of swaps is {}", swaps)
+            },
+        }
+    }
+}
+
+fn is_permutation(grid: &Grid) -> bool {
+    let mut used_positions = 0u32;
+
+    for (i, square) in grid.puzzle.squares.iter().enumerate() {
+        if grid::is_gap_position(i) {
+            continue;
+        }
+
+        let position = square.position;
+
+        if position >= WORD_LENGTH * WORD_LENGTH
+            || grid::is_gap_position(position)
+            || used_positions & (1 << position) != 0
+        {
+            return false;
+        }
+
+        used_positions |= 1 << position;
+    }
+
+    true
+}
+
+fn minimum_swaps(grid: &Grid) -> Option<usize> {
+    let puzzle = grid.puzzle
+        .squares
+        .iter()
+        .map(|square| grid.solution.letters[square.position])
+        .collect::<Vec<char>>();
+
+    swap_solver::minimum_swaps(&puzzle, &grid.solution.letters)
+}
+
+// Returns a list of the problems found with the puzzle. The list is
+// empty if the puzzle looks fine.
+pub fn check(grid: &Grid) -> Vec<Problem> {
+    let mut problems = Vec::new();
+
+    let mut words = HashSet::new();
+
+    for positions in grid::WordPositions::new() {
+        let word = positions
+            .map(|pos| grid.solution.letters[pos])
+            .collect::<String>();
+
+        if words.contains(&word) {
+            problems.push(Problem::DuplicateWord(word));
+        } else {
+            words.insert(word);
+        }
+    }
+
+    // The swap count can’t be worked out without a valid permutation
+    if !is_permutation(grid) {
+        problems.push(Problem::InvalidPermutation);
+        return problems;
+    }
+
+    match minimum_swaps(grid) {
+        Some(swaps) => {
+            if swaps == 0 || swaps > MAX_MINIMUM_SWAPS {
+                problems.push(Problem::MinimumSwaps(swaps));
+            }
+        },
+        None => problems.push(Problem::UnbalancedLetters),
+    }
+
+    problems
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn good_puzzle() {
+        let grid = "BATOJRAAINTERDUDIRILO\
+                    akdyetwfcnrlpjbmuohxv"
+            .parse::<Grid>()
+            .unwrap();
+
+        assert_eq!(check(&grid), Vec::new());
+    }
+
+    #[test]
+    fn duplicate_word() {
+        let grid = "MORSAOOOMORSAOOOMORSA\
+                    arcdnhfjvlmewpxbukoty"
+            .parse::<Grid>()
+            .unwrap();
+
+        let duplicates = check(&grid)
+            .into_iter()
+            .filter(|problem| matches!(problem, Problem::DuplicateWord(_)))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            duplicates,
+            vec![
+                Problem::DuplicateWord("MORSA".to_string()),
+                Problem::DuplicateWord("MORSA".to_string()),
+            ],
+        );
+    }
+
+    #[test]
+    fn bad_permutation() {
+        let mut grid = "MORSAUUKROLASDOOURSOJ\
+                        arcdnhfjvlmewpxbukoty"
+            .parse::<Grid>()
+            .unwrap();
+
+        grid.puzzle.squares[0].position = grid.puzzle.squares[1].position;
+
+        assert_eq!(check(&grid), vec![Problem::InvalidPermutation]);
+
+        grid.puzzle.squares[0].position = WORD_LENGTH + 1;
+
+        assert_eq!(check(&grid), vec![Problem::InvalidPermutation]);
+    }
+
+    #[test]
+    fn swap_count() {
+        // Already solved
+        let grid = "MORSAUUKROLASDOOURSOJ\
+                    abcdefhjklmnoprtuvwxy"
+            .parse::<Grid>()
+            .unwrap();
+
+        assert_eq!(check(&grid), vec![Problem::MinimumSwaps(0)]);
+
+        // Needs more swaps than the stars allow for
+        let grid = "ABCDEFHJKLMNOPRTUVWXY\
+                    bcdefhjklmnoprtuvwxya"
+            .parse::<Grid>()
+            .unwrap();
+
+        assert_eq!(check(&grid), vec![Problem::MinimumSwaps(20)]);
+    }
+}
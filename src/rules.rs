@@ -0,0 +1,272 @@
+// Vaflo – A word game in Esperanto
+// Copyright (C) 2025  Neil Roberts
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+// The rules that a puzzle has to follow before it can be published.
+// These are used by check-puzzles to check the whole puzzles file and
+// by the editor to show a checklist for the puzzle being edited.
+
+use std::fmt;
+use std::collections::{HashMap, hash_map};
+use super::letter_grid::{self, LetterGrid};
+use super::dictionary::Dictionary;
+use super::word_grid::WordGrid;
+use super::grid_solver::GridSolver;
+use super::grid::{self, Grid};
+use super::stem_word;
+use super::stars;
+
+// The number of swaps that the puzzle must need to be solved so that
+// the stars work out
+pub const REQUIRED_SWAPS: usize =
+    stars::MAXIMUM_SWAPS as usize - stars::MAXIMUM_STARS as usize;
+
+#[derive(Debug)]
+pub enum Problem {
+    LetterGridParseError(letter_grid::ParseError),
+    SolutionCount(usize),
+    NoSwapSolutionFound,
+    MinimumSwaps(usize),
+    BadWord(String),
+    DuplicateWord(String),
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Problem::LetterGridParseError(e) => write!(f, "{}", e),
+            Problem::SolutionCount(count) => {
+                write!(f, "puzzle has {} solutions", count)
+            },
+            Problem::NoSwapSolutionFound => {
+                write!(f, "no solution found by swapping letters")
+            },
+            Problem::MinimumSwaps(swaps) => {
+                write!(f, "minimum number of swaps is {}", swaps)
+            },
+            Problem::BadWord(word) => {
+                write!(f, "“{}” is not in the dictionary", word.to_uppercase())
+            },
+            Problem::DuplicateWord(word) => {
+                write!(f, "“{}” appears more than once", word.to_uppercase())
+            },
+        }
+    }
+}
+
+// Checks that all of the words are in the dictionary and that no two
+// words have the same stem
+pub fn check_words(dictionary: &Dictionary, grid: &Grid) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    let mut words = HashMap::new();
+
+    for positions in grid::WordPositions::new() {
+        let word_chars = positions.map(|pos| grid.solution.letters[pos]);
+        let word = || { word_chars.clone().collect::<String>() };
+
+        let mut stem = word();
+        let stem_len = stem_word::stem(&stem).len();
+        stem.truncate(stem_len);
+
+        match words.entry(stem) {
+            hash_map::Entry::Occupied(entry) => {
+                let counter = entry.into_mut();
+
+                if *counter == 1 {
+                    problems.push(Problem::DuplicateWord(word()));
+                }
+
+                *counter += 1;
+            },
+            hash_map::Entry::Vacant(entry) => {
+                if !dictionary.contains(word_chars.clone()) {
+                    problems.push(Problem::BadWord(word()));
+                }
+
+                entry.insert(1);
+            },
+        }
+    }
+
+    problems
+}
+
+pub fn count_solutions(grid: &LetterGrid, dictionary: &Dictionary) -> usize {
+    let mut solver = GridSolver::new(
+        WordGrid::new(grid),
+        dictionary,
+    );
+
+    let mut count = 0;
+
+    while solver.next().is_some() {
+        count += 1;
+    }
+
+    count
+}
+
+pub fn check_solution_count(count: usize) -> Option<Problem> {
+    (count != 1).then_some(Problem::SolutionCount(count))
+}
+
+// Makes the letter grid that the solver searches from the starting
+// position of the puzzle. The square states are worked out as in hard
+// mode so that only the letters in the right place are fixed. That
+// way a puzzle that passes the checks can also be solved when the
+// letters in the wrong position aren’t marked.
+pub fn hard_mode_letter_grid(
+    grid: &Grid,
+) -> Result<LetterGrid, letter_grid::ParseError> {
+    let mut grid = grid.clone();
+    grid.update_square_states_hard();
+    LetterGrid::from_grid(&grid)
+}
+
+// Checks that there is only one way to fill in the puzzle with words
+// from the dictionary using the letters that aren’t already in the
+// right place
+pub fn check_solutions(dictionary: &Dictionary, grid: &Grid) -> Option<Problem> {
+    match hard_mode_letter_grid(grid) {
+        Ok(letter_grid) => {
+            check_solution_count(count_solutions(&letter_grid, dictionary))
+        },
+        Err(e) => Some(Problem::LetterGridParseError(e)),
+    }
+}
+
+// Checks the result of the swap solver. `None` means that no way to
+// reach the solution by swapping letters was found.
+pub fn check_minimum_swaps(swaps: Option<usize>) -> Option<Problem> {
+    match swaps {
+        Some(swaps) => {
+            (swaps != REQUIRED_SWAPS).then_some(Problem::MinimumSwaps(swaps))
+        },
+        None => Some(Problem::NoSwapSolutionFound),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use super::super::dictionary::test_data;
+
+    #[test]
+    fn words() {
+        let dictionary = test_data::grid_words();
+
+        let grid = "ABCDEFHJKLMNOPRTUVWXY\
+                    abcdefhjklmnoprtuvwxy"
+            .parse::<Grid>()
+            .unwrap();
+
+        assert!(check_words(&dictionary, &grid).is_empty());
+
+        let grid = "ABCDEFHJKLMNOPRTUVWXZ\
+                    abcdefhjklmnoprtuvwxy"
+            .parse::<Grid>()
+            .unwrap();
+
+        assert_eq!(
+            check_words(&dictionary, &grid)
+                .iter()
+                .map(Problem::to_string)
+                .collect::<Vec<_>>(),
+            vec![
+                "“UVWXZ” is not in the dictionary",
+                "“EJOTZ” is not in the dictionary",
+            ],
+        );
+
+        let grid = "KATOJOOOKATONOOOKATAJ\
+                    abcdefhjklmnoprtuvwxy"
+            .parse::<Grid>()
+            .unwrap();
+
+        let duplicates = check_words(&dictionary, &grid)
+            .into_iter()
+            .filter_map(|problem| match problem {
+                Problem::DuplicateWord(word) => Some(word),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(duplicates, vec!["KATON"]);
+    }
+
+    #[test]
+    fn solutions() {
+        let dictionary = test_data::grid_words();
+
+        // Everything is in the right place so there’s only one way
+        // to fill the grid
+        let grid = "ABCDEFHJKLMNOPRTUVWXY\
+                    abcdefhjklmnoprtuvwxy"
+            .parse::<Grid>()
+            .unwrap();
+
+        assert!(check_solutions(&dictionary, &grid).is_none());
+
+        // There’s no way to make the last word with these letters
+        let grid = "ABCDEFHJKLMNOPRTUVWXZ\
+                    abcdefhjklmnoprtuvwxy"
+            .parse::<Grid>()
+            .unwrap();
+
+        assert!(matches!(
+            check_solutions(&dictionary, &grid),
+            Some(Problem::SolutionCount(0)),
+        ));
+
+        // Swap two letters within the first word so that they are
+        // shown as in the wrong position in the normal mode. Hard
+        // mode doesn’t show that they belong in that word, but the
+        // other letters still only make one set of words.
+        let mut grid = "ABCDEFHJKLMNOPRTUVWXY\
+                        abcdefhjklmnoprtuvwxy"
+            .parse::<Grid>()
+            .unwrap();
+        grid.puzzle.squares.swap(0, 1);
+        grid.update_square_states();
+
+        assert_eq!(
+            grid.puzzle.squares[0].state,
+            grid::PuzzleSquareState::WrongPosition,
+        );
+
+        let letter_grid = hard_mode_letter_grid(&grid).unwrap();
+        assert!(letter_grid.to_string().starts_with("baCDE\n"));
+        assert!(check_solutions(&dictionary, &grid).is_none());
+
+        assert!(check_solution_count(1).is_none());
+        assert!(matches!(
+            check_solution_count(0),
+            Some(Problem::SolutionCount(0)),
+        ));
+    }
+
+    #[test]
+    fn swaps() {
+        assert!(check_minimum_swaps(Some(REQUIRED_SWAPS)).is_none());
+        assert!(matches!(
+            check_minimum_swaps(Some(REQUIRED_SWAPS - 1)),
+            Some(Problem::MinimumSwaps(swaps)) if swaps == REQUIRED_SWAPS - 1,
+        ));
+        assert!(matches!(
+            check_minimum_swaps(None),
+            Some(Problem::NoSwapSolutionFound),
+        ));
+    }
+}
@@ -18,14 +18,26 @@ use std::fmt;
 use fmt::Write;
 use super::grid;
 use grid::Grid;
-use grid::PuzzleSquareState;
 use std::str::FromStr;
 use std::collections::HashMap;
 use super::stars::{MAXIMUM_SWAPS, MAXIMUM_STARS};
+use super::settings::{Settings, StreakRules};
+use super::date::{self, Date};
+use super::share_template::ShareTemplate;
 
+#[derive(Clone)]
 pub struct SaveState {
     grid: Grid,
     swaps_remaining: u32,
+    hints_used: u32,
+    // Number of seconds spent on the puzzle since the first swap
+    time_taken: Option<u32>,
+    // Whether the puzzle was played in hard mode
+    hard_mode: bool,
+    // The number of the daily puzzle on the day that this puzzle was
+    // last played. This can be different from the puzzle number if
+    // it was played later from the archive.
+    played_on: Option<usize>,
 }
 
 #[derive(Debug)]
@@ -33,6 +45,11 @@ pub enum ParseError {
     MissingColon,
     InvalidGrid(grid::GridParseError),
     InvalidSwapsRemaining,
+    InvalidHintsUsed,
+    InvalidTimeTaken,
+    InvalidHardMode,
+    InvalidPlayedOn,
+    UnknownField(char),
 }
 
 #[derive(Debug)]
@@ -57,13 +74,52 @@ impl SaveState {
     pub fn new(grid: Grid, swaps_remaining: u32) -> SaveState {
         assert!(swaps_remaining <= MAXIMUM_SWAPS);
 
-        SaveState { grid, swaps_remaining }
+        SaveState {
+            grid,
+            swaps_remaining,
+            hints_used: 0,
+            time_taken: None,
+            hard_mode: false,
+            played_on: None,
+        }
     }
 
     pub fn swaps_remaining(&self) -> u32 {
         self.swaps_remaining
     }
 
+    pub fn hints_used(&self) -> u32 {
+        self.hints_used
+    }
+
+    pub fn set_hints_used(&mut self, hints_used: u32) {
+        self.hints_used = hints_used;
+    }
+
+    pub fn time_taken(&self) -> Option<u32> {
+        self.time_taken
+    }
+
+    pub fn set_time_taken(&mut self, time_taken: Option<u32>) {
+        self.time_taken = time_taken;
+    }
+
+    pub fn hard_mode(&self) -> bool {
+        self.hard_mode
+    }
+
+    pub fn set_hard_mode(&mut self, hard_mode: bool) {
+        self.hard_mode = hard_mode;
+    }
+
+    pub fn played_on(&self) -> Option<usize> {
+        self.played_on
+    }
+
+    pub fn set_played_on(&mut self, played_on: Option<usize>) {
+        self.played_on = played_on;
+    }
+
     pub fn grid(&self) -> &Grid {
         &self.grid
     }
@@ -71,7 +127,27 @@ impl SaveState {
 
 impl fmt::Display for SaveState {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}:{}", self.grid, self.swaps_remaining)
+        write!(f, "{}:{}", self.grid, self.swaps_remaining)?;
+
+        // Optional extra fields are each prefixed with a letter so
+        // that older save states without them can still be parsed
+        if self.hints_used > 0 {
+            write!(f, ":h{}", self.hints_used)?;
+        }
+
+        if let Some(time_taken) = self.time_taken {
+            write!(f, ":t{}", time_taken)?;
+        }
+
+        if self.hard_mode {
+            write!(f, ":d")?;
+        }
+
+        if let Some(played_on) = self.played_on {
+            write!(f, ":p{}", played_on)?;
+        }
+
+        Ok(())
     }
 }
 
@@ -83,6 +159,21 @@ impl fmt::Display for ParseError {
             ParseError::InvalidSwapsRemaining => {
                 write!(f, "the number of swaps remaining is invalid")
             },
+            ParseError::InvalidHintsUsed => {
+                write!(f, "the number of hints used is invalid")
+            },
+            ParseError::InvalidTimeTaken => {
+                write!(f, "the time taken is invalid")
+            },
+            ParseError::InvalidHardMode => {
+                write!(f, "the hard mode field is invalid")
+            },
+            ParseError::InvalidPlayedOn => {
+                write!(f, "the day the puzzle was played is invalid")
+            },
+            ParseError::UnknownField(key) => {
+                write!(f, "unknown field “{}”", key)
+            },
         }
     }
 }
@@ -116,14 +207,16 @@ impl FromStr for SaveState {
     type Err = ParseError;
 
     fn from_str(s: &str) -> Result<SaveState, ParseError> {
-        let Some((grid, swaps_remaining)) = s.split_once(':')
+        let Some((grid, fields)) = s.split_once(':')
         else {
             return Err(ParseError::MissingColon);
         };
 
         let grid = grid.parse::<Grid>()?;
 
-        let Ok(swaps_remaining) = swaps_remaining.parse::<u32>()
+        let mut fields = fields.split(':');
+
+        let Some(Ok(swaps_remaining)) = fields.next().map(str::parse::<u32>)
         else {
             return Err(ParseError::InvalidSwapsRemaining);
         };
@@ -132,7 +225,49 @@ impl FromStr for SaveState {
             return Err(ParseError::InvalidSwapsRemaining);
         }
 
-        Ok(SaveState::new(grid, swaps_remaining))
+        let mut save_state = SaveState::new(grid, swaps_remaining);
+
+        for field in fields {
+            let mut chars = field.chars();
+
+            match chars.next() {
+                Some('h') => {
+                    let Ok(hints_used) = chars.as_str().parse::<u32>()
+                    else {
+                        return Err(ParseError::InvalidHintsUsed);
+                    };
+
+                    save_state.hints_used = hints_used;
+                },
+                Some('t') => {
+                    let Ok(time_taken) = chars.as_str().parse::<u32>()
+                    else {
+                        return Err(ParseError::InvalidTimeTaken);
+                    };
+
+                    save_state.time_taken = Some(time_taken);
+                },
+                Some('d') => {
+                    if !chars.as_str().is_empty() {
+                        return Err(ParseError::InvalidHardMode);
+                    }
+
+                    save_state.hard_mode = true;
+                },
+                Some('p') => {
+                    let Ok(played_on) = chars.as_str().parse::<usize>()
+                    else {
+                        return Err(ParseError::InvalidPlayedOn);
+                    };
+
+                    save_state.played_on = Some(played_on);
+                },
+                Some(key) => return Err(ParseError::UnknownField(key)),
+                None => return Err(ParseError::UnknownField(' ')),
+            }
+        }
+
+        Ok(save_state)
     }
 }
 
@@ -152,37 +287,155 @@ where
     result
 }
 
+fn parse_day_string(
+    state_num: usize,
+    day_string: &str,
+    states: &HashMap<usize, SaveState>,
+) -> Result<(usize, SaveState), LoadSaveStatesError> {
+    let Some((puzzle_num, state_string)) = day_string.split_once(':')
+    else {
+        return Err(LoadSaveStatesError::MissingColon(state_num));
+    };
+
+    let Ok(puzzle_num) = puzzle_num.parse::<usize>()
+    else {
+        return Err(LoadSaveStatesError::InvalidPuzzleNumber(state_num));
+    };
+
+    if states.contains_key(&puzzle_num) {
+        return Err(LoadSaveStatesError::DuplicatePuzzle(puzzle_num));
+    }
+
+    match state_string.parse::<SaveState>() {
+        Ok(save_state) => Ok((puzzle_num, save_state)),
+        Err(e) => Err(LoadSaveStatesError::BadPuzzle(puzzle_num, e)),
+    }
+}
+
 pub fn load_save_states(
     s: &str,
 ) -> Result<HashMap<usize, SaveState>, LoadSaveStatesError> {
     let mut states = HashMap::new();
 
     for (state_num, day_string) in s.split(',').enumerate() {
-        let Some((puzzle_num, state_string)) = day_string.split_once(':')
-        else {
-            return Err(LoadSaveStatesError::MissingColon(state_num));
-        };
+        let (puzzle_num, save_state) =
+            parse_day_string(state_num, day_string, &states)?;
+        states.insert(puzzle_num, save_state);
+    }
 
-        let Ok(puzzle_num) = puzzle_num.parse::<usize>()
-        else {
-            return Err(LoadSaveStatesError::InvalidPuzzleNumber(state_num));
-        };
+    Ok(states)
+}
+
+// A part of the save states string that couldn’t be loaded by
+// load_save_states_lenient
+pub struct DroppedSaveState {
+    pub fragment: String,
+    pub error: LoadSaveStatesError,
+}
 
-        if states.contains_key(&puzzle_num) {
-            return Err(LoadSaveStatesError::DuplicatePuzzle(puzzle_num));
+// Like load_save_states except that instead of failing on the first
+// bad entry it skips it and carries on. The entries that couldn’t be
+// parsed are returned along with the reason so that they can be
+// reported and kept somewhere else. If a puzzle appears more than
+// once then the first entry is used.
+pub fn load_save_states_lenient(
+    s: &str,
+) -> (HashMap<usize, SaveState>, Vec<DroppedSaveState>) {
+    let mut states = HashMap::new();
+    let mut dropped = Vec::new();
+
+    for (state_num, day_string) in s.split(',').enumerate() {
+        // Empty fragments don’t contain anything worth keeping
+        if day_string.is_empty() {
+            continue;
         }
 
-        match state_string.parse::<SaveState>() {
-            Ok(save_state) => {
+        match parse_day_string(state_num, day_string, &states) {
+            Ok((puzzle_num, save_state)) => {
                 states.insert(puzzle_num, save_state);
             },
-            Err(e) => {
-                return Err(LoadSaveStatesError::BadPuzzle(puzzle_num, e));
+            Err(error) => {
+                dropped.push(DroppedSaveState {
+                    fragment: day_string.to_string(),
+                    error,
+                });
             },
         }
     }
 
-    Ok(states)
+    (states, dropped)
+}
+
+pub fn format_time(seconds: u32) -> String {
+    let minutes = seconds / 60;
+    let hours = minutes / 60;
+
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes % 60, seconds % 60)
+    } else {
+        format!("{}:{:02}", minutes, seconds % 60)
+    }
+}
+
+// Statistics for the finished puzzles within a period such as a month
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PeriodStatistics {
+    n_finished: u32,
+    n_solved: u32,
+    total_stars: u32,
+    total_swaps_used: u32,
+}
+
+pub struct MonthStatistics {
+    pub year: i32,
+    pub month: u32,
+    pub statistics: PeriodStatistics,
+}
+
+impl PeriodStatistics {
+    fn add(&mut self, save_state: &SaveState) {
+        if save_state.grid().puzzle.is_solved() {
+            self.n_finished += 1;
+            self.n_solved += 1;
+            self.total_stars += save_state.swaps_remaining()
+                .min(MAXIMUM_STARS);
+            self.total_swaps_used +=
+                MAXIMUM_SWAPS - save_state.swaps_remaining();
+        } else if save_state.swaps_remaining() == 0 {
+            self.n_finished += 1;
+            self.total_swaps_used += MAXIMUM_SWAPS;
+        }
+    }
+
+    // Number of puzzles that were either solved or failed
+    pub fn n_finished(&self) -> u32 {
+        self.n_finished
+    }
+
+    pub fn n_solved(&self) -> u32 {
+        self.n_solved
+    }
+
+    // Average number of stars per finished puzzle where a failed
+    // puzzle counts as zero stars
+    pub fn average_stars(&self) -> Option<f32> {
+        (self.n_finished > 0).then(|| {
+            self.total_stars as f32 / self.n_finished as f32
+        })
+    }
+
+    pub fn average_swaps_used(&self) -> Option<f32> {
+        (self.n_finished > 0).then(|| {
+            self.total_swaps_used as f32 / self.n_finished as f32
+        })
+    }
+
+    // Percentage of the finished puzzles that were solved
+    pub fn win_rate(&self) -> Option<u32> {
+        (self.n_finished > 0).then(|| {
+            (self.n_solved * 100 + self.n_finished / 2) / self.n_finished
+        })
+    }
 }
 
 pub struct Statistics {
@@ -192,11 +445,35 @@ pub struct Statistics {
     total_stars: u32,
     current_streak: u32,
     best_streak: u32,
+    best_time: Option<u32>,
+    average_time: Option<u32>,
+    best_perfect_run: u32,
+    overall: PeriodStatistics,
+    months: Vec<MonthStatistics>,
+    weekdays: [PeriodStatistics; date::N_WEEKDAYS],
+}
+
+// Whether a solved puzzle should count towards the streak
+fn counts_for_streak(
+    rules: &StreakRules,
+    puzzle_num: usize,
+    save_state: &SaveState,
+) -> bool {
+    // Save states from before the day was recorded are assumed to
+    // have been played on the day
+    !rules.same_day_only
+        || save_state.played_on().map(|day| day == puzzle_num).unwrap_or(true)
 }
 
 impl Statistics {
-    pub fn new(save_states: &HashMap<usize, SaveState>) -> Statistics
-    {
+    // `today` is the number of today’s puzzle. The current streak
+    // is only kept if the last puzzle that counted towards it is
+    // recent enough compared to today according to the rules.
+    pub fn new(
+        save_states: &HashMap<usize, SaveState>,
+        today: usize,
+        rules: &StreakRules,
+    ) -> Statistics {
         let mut puzzles = save_states
             .iter()
             .map(|(&k, v)| (k, v))
@@ -208,41 +485,118 @@ impl Statistics {
         let mut fail_count = 0;
         let n_played = puzzles.len() as u32;
         let mut total_stars = 0;
-        let mut current_streak = 0;
+        let mut streak = 0;
         let mut best_streak = 0;
+        // The last puzzle that counted towards the streak
+        let mut last_streak_puzzle = None::<usize>;
         let mut last_puzzle_num = None;
+        let mut best_time = None::<u32>;
+        let mut total_time = 0u64;
+        let mut n_timed = 0u64;
+        let mut perfect_run = 0;
+        let mut best_perfect_run = 0;
+        let mut overall = PeriodStatistics::default();
+        let mut months = Vec::<MonthStatistics>::new();
+        let mut weekdays = [PeriodStatistics::default(); date::N_WEEKDAYS];
+
+        // Whether the streak can continue on the given puzzle without
+        // missing more days than the rules allow
+        let streak_alive = |last_streak_puzzle: Option<usize>, puzzle_num| {
+            last_streak_puzzle.map(|last_streak_puzzle| {
+                puzzle_num
+                    <= last_streak_puzzle + 1 + rules.grace_days as usize
+            }).unwrap_or(false)
+        };
 
         for (puzzle_num, save_state) in puzzles {
-            let streak_continued = last_puzzle_num.map(|last_puzzle_num| {
+            let run_continued = last_puzzle_num.map(|last_puzzle_num| {
                 last_puzzle_num + 1 == puzzle_num
             }).unwrap_or(false);
 
-            if !streak_continued {
-                current_streak = 0;
+            if !run_continued {
+                perfect_run = 0;
+            }
+
+            let date = Date::for_puzzle(puzzle_num);
+
+            // The puzzles are sorted so a new month can only be at the end
+            match months.last_mut() {
+                Some(month) if month.year == date.year
+                    && month.month == date.month =>
+                {
+                    month.statistics.add(save_state);
+                },
+                _ => {
+                    let mut statistics = PeriodStatistics::default();
+                    statistics.add(save_state);
+                    months.push(MonthStatistics {
+                        year: date.year,
+                        month: date.month,
+                        statistics,
+                    });
+                },
             }
 
+            weekdays[date.weekday()].add(save_state);
+            overall.add(save_state);
+
             if save_state.grid().puzzle.is_solved() {
-                current_streak += 1;
+                // Solved puzzles that don’t count are treated the
+                // same as a missed day
+                if counts_for_streak(rules, puzzle_num, save_state) {
+                    if !streak_alive(last_streak_puzzle, puzzle_num) {
+                        streak = 0;
+                    }
 
-                if current_streak > best_streak {
-                    best_streak = current_streak;
+                    streak += 1;
+                    last_streak_puzzle = Some(puzzle_num);
+
+                    if streak > best_streak {
+                        best_streak = streak;
+                    }
                 }
 
                 let stars = save_state.swaps_remaining().min(MAXIMUM_STARS);
 
                 star_counts[stars as usize] += 1;
                 total_stars += stars;
+
+                if stars >= MAXIMUM_STARS {
+                    perfect_run += 1;
+                    best_perfect_run = best_perfect_run.max(perfect_run);
+                } else {
+                    perfect_run = 0;
+                }
+
+                if let Some(time_taken) = save_state.time_taken() {
+                    best_time = Some(match best_time {
+                        Some(best_time) => best_time.min(time_taken),
+                        None => time_taken,
+                    });
+                    total_time += time_taken as u64;
+                    n_timed += 1;
+                }
             } else {
-                current_streak = 0;
+                perfect_run = 0;
 
+                // A failed puzzle always breaks the streak whereas
+                // an unfinished one is the same as a missed day
                 if save_state.swaps_remaining() <= 0 {
                     fail_count += 1;
+                    streak = 0;
+                    last_streak_puzzle = None;
                 }
             }
 
             last_puzzle_num = Some(puzzle_num);
         }
 
+        let current_streak = if streak_alive(last_streak_puzzle, today) {
+            streak
+        } else {
+            0
+        };
+
         Statistics {
             star_counts,
             fail_count,
@@ -250,6 +604,14 @@ impl Statistics {
             total_stars,
             current_streak,
             best_streak,
+            best_time,
+            average_time: (n_timed > 0).then(|| {
+                ((total_time + n_timed / 2) / n_timed) as u32
+            }),
+            best_perfect_run,
+            overall,
+            months,
+            weekdays,
         }
     }
 
@@ -277,16 +639,60 @@ impl Statistics {
         self.best_streak
     }
 
+    // Fastest time in seconds of all the solved puzzles that were timed
+    pub fn best_time(&self) -> Option<u32> {
+        self.best_time
+    }
+
+    pub fn average_time(&self) -> Option<u32> {
+        self.average_time
+    }
+
+    // Longest run of consecutive puzzles solved with all the stars
+    pub fn best_perfect_run(&self) -> u32 {
+        self.best_perfect_run
+    }
+
+    pub fn overall(&self) -> &PeriodStatistics {
+        &self.overall
+    }
+
+    // Statistics for each month that has a played puzzle, in order
+    pub fn months(&self) -> &[MonthStatistics] {
+        &self.months
+    }
+
+    // Statistics for each day of the week where 0 is Monday
+    pub fn weekdays(&self) -> &[PeriodStatistics; date::N_WEEKDAYS] {
+        &self.weekdays
+    }
+
     pub fn share_text(
         &self,
         puzzle_num: usize,
         save_state: &SaveState,
+        settings: &Settings,
+    ) -> String {
+        self.share_text_with_template(
+            puzzle_num,
+            save_state,
+            settings,
+            &ShareTemplate::default(),
+        )
+    }
+
+    pub fn share_text_with_template(
+        &self,
+        puzzle_num: usize,
+        save_state: &SaveState,
+        settings: &Settings,
+        template: &ShareTemplate,
     ) -> String {
         let mut results = String::new();
 
         let is_solved = save_state.grid.puzzle.is_solved();
 
-        write!(results, "#vaflo{} ", puzzle_num + 1).unwrap();
+        write!(results, "{}{} ", template.hashtag, puzzle_num + 1).unwrap();
 
         if is_solved {
             write!(results, "{}", save_state.swaps_remaining).unwrap();
@@ -294,51 +700,75 @@ impl Statistics {
             results.push('X');
         }
 
-        write!(results, "/{}\n\n", MAXIMUM_STARS).unwrap();
+        write!(results, "/{}", MAXIMUM_STARS).unwrap();
 
-        let star_positions = STAR_POSITIONS[
-            if is_solved {
-                save_state.swaps_remaining.min(MAXIMUM_STARS) as usize
-            } else {
-                0
-            }
-        ];
+        if save_state.hard_mode {
+            results.push('*');
+        }
+
+        for _ in 0..save_state.hints_used {
+            results.push_str(" 💡");
+        }
 
-        for y in 0..grid::WORD_LENGTH {
-            for x in 0..grid::WORD_LENGTH {
-                let position = y * grid::WORD_LENGTH + x;
+        results.push_str("\n\n");
 
-                let ch = if star_positions & (1 << position) != 0 {
-                    '⭐'
-                } else if grid::is_gap_space(x as i32, y as i32) {
-                    '⬜'
+        if template.include_grid {
+            let star_positions = STAR_POSITIONS[
+                if is_solved && template.include_stars {
+                    save_state.swaps_remaining.min(MAXIMUM_STARS) as usize
                 } else {
-                    match save_state.grid.puzzle.squares[position].state {
-                        PuzzleSquareState::Correct => '🟩',
-                        PuzzleSquareState::WrongPosition
-                            | PuzzleSquareState::Wrong => '⬛',
-                    }
-                };
+                    0
+                }
+            ];
+
+            for y in 0..grid::WORD_LENGTH {
+                for x in 0..grid::WORD_LENGTH {
+                    let position = y * grid::WORD_LENGTH + x;
+
+                    let ch = if star_positions & (1 << position) != 0 {
+                        '⭐'
+                    } else if grid::is_gap_space(x as i32, y as i32) {
+                        '⬜'
+                    } else {
+                        settings.palette.share_emoji(
+                            save_state.grid.puzzle.squares[position].state
+                        )
+                    };
+
+                    results.push(ch);
+                }
 
-                results.push(ch);
+                results.push('\n');
             }
 
             results.push('\n');
         }
 
-        write!(
+        writeln!(
             results,
-            "\n\
-             {} gajnvico: {}\n\
-             https://vaflo.net",
+            "{} {}: {}",
             if is_solved {
                 '🔥'
             } else {
                 '💔'
             },
+            template.streak_label,
             self.current_streak(),
         ).unwrap();
 
+        if let Some(time_taken) = save_state.time_taken()
+            .filter(|_| template.include_time && settings.timer && is_solved)
+        {
+            writeln!(results, "⏱️ {}", format_time(time_taken)).unwrap();
+        }
+
+        if template.url.is_empty() {
+            // Remove the trailing newline
+            results.pop();
+        } else {
+            results.push_str(&template.url);
+        }
+
         results
     }
 }
@@ -346,6 +776,7 @@ impl Statistics {
 #[cfg(test)]
 mod test {
     use super::*;
+    use super::super::settings::Palette;
 
     #[test]
     fn bad_string() {
@@ -393,6 +824,138 @@ mod test {
         );
 
         assert_eq!(save_state.swaps_remaining(), 14);
+        assert_eq!(save_state.hints_used(), 0);
+    }
+
+    #[test]
+    fn parse_hints() {
+        let state_string = "ABCDEFHJKLMNOPRTUVWXY\
+                            bacdefhjklmnoprtuvwxy\
+                            :12:h2";
+
+        let save_state = state_string.parse::<SaveState>().unwrap();
+
+        assert_eq!(save_state.swaps_remaining(), 12);
+        assert_eq!(save_state.hints_used(), 2);
+        assert_eq!(state_string, &save_state.to_string());
+
+        assert!(matches!(
+            "ABCDEFHJKLMNOPRTUVWXY\
+             bacdefhjklmnoprtuvwxy\
+             :12:hx".parse::<SaveState>(),
+            Err(ParseError::InvalidHintsUsed),
+        ));
+
+        assert!(matches!(
+            "ABCDEFHJKLMNOPRTUVWXY\
+             bacdefhjklmnoprtuvwxy\
+             :12:q3".parse::<SaveState>(),
+            Err(ParseError::UnknownField('q')),
+        ));
+    }
+
+    #[test]
+    fn parse_time() {
+        let state_string = "ABCDEFHJKLMNOPRTUVWXY\
+                            bacdefhjklmnoprtuvwxy\
+                            :12:h1:t95";
+
+        let save_state = state_string.parse::<SaveState>().unwrap();
+
+        assert_eq!(save_state.hints_used(), 1);
+        assert_eq!(save_state.time_taken(), Some(95));
+        assert_eq!(state_string, &save_state.to_string());
+
+        let save_state = "ABCDEFHJKLMNOPRTUVWXY\
+                          bacdefhjklmnoprtuvwxy\
+                          :12".parse::<SaveState>().unwrap();
+        assert_eq!(save_state.time_taken(), None);
+
+        assert!(matches!(
+            "ABCDEFHJKLMNOPRTUVWXY\
+             bacdefhjklmnoprtuvwxy\
+             :12:t-3".parse::<SaveState>(),
+            Err(ParseError::InvalidTimeTaken),
+        ));
+    }
+
+    #[test]
+    fn parse_hard_mode() {
+        let state_string = "ABCDEFHJKLMNOPRTUVWXY\
+                            bacdefhjklmnoprtuvwxy\
+                            :12:t95:d";
+
+        let save_state = state_string.parse::<SaveState>().unwrap();
+
+        assert!(save_state.hard_mode());
+        assert_eq!(state_string, &save_state.to_string());
+
+        let save_state = "ABCDEFHJKLMNOPRTUVWXY\
+                          bacdefhjklmnoprtuvwxy\
+                          :12".parse::<SaveState>().unwrap();
+        assert!(!save_state.hard_mode());
+
+        assert!(matches!(
+            "ABCDEFHJKLMNOPRTUVWXY\
+             bacdefhjklmnoprtuvwxy\
+             :12:d1".parse::<SaveState>(),
+            Err(ParseError::InvalidHardMode),
+        ));
+    }
+
+    #[test]
+    fn parse_played_on() {
+        let state_string = "ABCDEFHJKLMNOPRTUVWXY\
+                            bacdefhjklmnoprtuvwxy\
+                            :12:p42";
+
+        let save_state = state_string.parse::<SaveState>().unwrap();
+
+        assert_eq!(save_state.played_on(), Some(42));
+        assert_eq!(state_string, &save_state.to_string());
+
+        assert!(matches!(
+            "ABCDEFHJKLMNOPRTUVWXY\
+             bacdefhjklmnoprtuvwxy\
+             :12:px".parse::<SaveState>(),
+            Err(ParseError::InvalidPlayedOn),
+        ));
+    }
+
+    #[test]
+    fn display_extra_fields() {
+        let grid = "ABCDEFHJKLMNOPRTUVWXY\
+                    bacdefhjklmnoprtuvwxy"
+            .parse::<Grid>()
+            .unwrap();
+        let mut save_state = SaveState::new(grid, 9);
+
+        save_state.set_hints_used(3);
+        assert_eq!(
+            &save_state.to_string(),
+            "ABCDEFHJKLMNOPRTUVWXY\
+             bacdefhjklmnoprtuvwxy\
+             :9:h3",
+        );
+
+        save_state.set_hints_used(0);
+        save_state.set_time_taken(Some(42));
+        assert_eq!(
+            &save_state.to_string(),
+            "ABCDEFHJKLMNOPRTUVWXY\
+             bacdefhjklmnoprtuvwxy\
+             :9:t42",
+        );
+    }
+
+    #[test]
+    fn test_format_time() {
+        assert_eq!(&format_time(0), "0:00");
+        assert_eq!(&format_time(59), "0:59");
+        assert_eq!(&format_time(83), "1:23");
+        assert_eq!(&format_time(3599), "59:59");
+        assert_eq!(&format_time(3600), "1:00:00");
+        assert_eq!(&format_time(3723), "1:02:03");
     }
 
     #[test]
@@ -437,6 +1000,78 @@ mod test {
         ));
     }
 
+    #[test]
+    fn lenient_load() {
+        let (states, dropped) = load_save_states_lenient("");
+        assert!(states.is_empty());
+        assert!(dropped.is_empty());
+
+        let (states, dropped) = load_save_states_lenient(
+            "0:\
+             ABCDEFHJKLMNOPRTUVWXY\
+             bacdefhjklmnoprtuvwxy:\
+             10,\
+             foo:\
+             ABCDEFHJKLMNOPRTUVWXY\
+             bacdefhjklmnoprtuvwxy:\
+             11,\
+             garbage,\
+             ,\
+             0:\
+             ABCDEFHJKLMNOPRTUVWXY\
+             abcdefhjklmnoprtuvwxy:\
+             9,\
+             3:\
+             ABCDEFHJKLMNOPRTUVWXY\
+             bacdefhjklmnopr,\
+             5:\
+             ABCDEFHJKLMNOPRTUVWXY\
+             abcdefhjklmnoprtuvwxy:\
+             12:h1"
+        );
+
+        let mut keys = states.keys().map(|&x| x).collect::<Vec<_>>();
+        keys.sort_unstable();
+        assert_eq!(&keys, &[0, 5]);
+
+        // The first entry for a duplicate puzzle wins
+        assert_eq!(states[&0].swaps_remaining(), 10);
+        assert_eq!(states[&5].swaps_remaining(), 12);
+        assert_eq!(states[&5].hints_used(), 1);
+
+        assert_eq!(dropped.len(), 4);
+
+        assert!(dropped[0].fragment.starts_with("foo:"));
+        assert!(matches!(
+            dropped[0].error,
+            LoadSaveStatesError::InvalidPuzzleNumber(1),
+        ));
+
+        assert_eq!(&dropped[1].fragment, "garbage");
+        assert!(matches!(
+            dropped[1].error,
+            LoadSaveStatesError::MissingColon(2),
+        ));
+
+        assert!(dropped[2].fragment.ends_with(":9"));
+        assert!(matches!(
+            dropped[2].error,
+            LoadSaveStatesError::DuplicatePuzzle(0),
+        ));
+
+        assert!(matches!(
+            dropped[3].error,
+            LoadSaveStatesError::BadPuzzle(3, ParseError::MissingColon),
+        ));
+
+        // Nothing is dropped if the string is valid
+        let (states, dropped) = load_save_states_lenient(
+            &save_states_to_string(states)
+        );
+        assert_eq!(states.len(), 2);
+        assert!(dropped.is_empty());
+    }
+
     #[test]
     fn test_load_save_states() {
         let save_states_string =
@@ -519,6 +1154,13 @@ mod test {
         add_unsolved(puzzle_num, 1, buf);
     }
 
+    // Makes the statistics with the default rules as if today’s
+    // puzzle were the last one in the save states
+    fn statistics_for(save_states: &HashMap<usize, SaveState>) -> Statistics {
+        let today = save_states.keys().max().map(|&x| x).unwrap_or(0);
+        Statistics::new(save_states, today, &StreakRules::default())
+    }
+
     fn add_solved(puzzle_num: usize, n_stars: usize, buf: &mut String) {
         add_puzzle(
             puzzle_num,
@@ -545,7 +1187,7 @@ mod test {
         add_solved(7, 2, &mut buf);
         add_solved(8, 3, &mut buf);
 
-        let statistics = Statistics::new(&load_save_states(&buf).unwrap());
+        let statistics = statistics_for(&load_save_states(&buf).unwrap());
 
         assert_eq!(statistics.best_streak(), 6);
         assert_eq!(statistics.current_streak(), 2);
@@ -579,7 +1221,7 @@ mod test {
 
         add_solved(7, 2, &mut buf);
 
-        let statistics = Statistics::new(&load_save_states(&buf).unwrap());
+        let statistics = statistics_for(&load_save_states(&buf).unwrap());
 
         assert_eq!(statistics.best_streak(), 2);
         assert_eq!(statistics.current_streak(), 1);
@@ -587,6 +1229,186 @@ mod test {
         assert_eq!(statistics.total_stars(), 4 + 5 + 2);
     }
 
+    #[test]
+    fn streak_relative_to_today() {
+        let mut buf = String::new();
+
+        add_solved(3, 4, &mut buf);
+        add_solved(4, 4, &mut buf);
+        add_solved(5, 4, &mut buf);
+
+        let save_states = load_save_states(&buf).unwrap();
+        let rules = StreakRules::default();
+
+        // Today’s puzzle hasn’t been played yet
+        let statistics = Statistics::new(&save_states, 6, &rules);
+        assert_eq!(statistics.current_streak(), 3);
+
+        // A day was missed
+        let statistics = Statistics::new(&save_states, 7, &rules);
+        assert_eq!(statistics.current_streak(), 0);
+        assert_eq!(statistics.best_streak(), 3);
+
+        // …but it’s allowed with a grace day
+        let rules = StreakRules { grace_days: 1, same_day_only: false };
+        let statistics = Statistics::new(&save_states, 7, &rules);
+        assert_eq!(statistics.current_streak(), 3);
+        let statistics = Statistics::new(&save_states, 8, &rules);
+        assert_eq!(statistics.current_streak(), 0);
+    }
+
+    #[test]
+    fn streak_grace_days() {
+        let mut buf = String::new();
+
+        add_solved(0, 4, &mut buf);
+        add_solved(1, 4, &mut buf);
+        // Missed days 2 and 3
+        add_solved(4, 4, &mut buf);
+        // Unfinished puzzles count as a missed day
+        add_unfinished(5, &mut buf);
+        add_solved(6, 4, &mut buf);
+        add_fail(7, &mut buf);
+        add_solved(8, 4, &mut buf);
+
+        let save_states = load_save_states(&buf).unwrap();
+
+        let statistics = Statistics::new(
+            &save_states,
+            8,
+            &StreakRules { grace_days: 1, same_day_only: false },
+        );
+        assert_eq!(statistics.best_streak(), 2);
+        assert_eq!(statistics.current_streak(), 1);
+
+        let statistics = Statistics::new(
+            &save_states,
+            8,
+            &StreakRules { grace_days: 2, same_day_only: false },
+        );
+        // A fail still breaks the streak
+        assert_eq!(statistics.best_streak(), 4);
+        assert_eq!(statistics.current_streak(), 1);
+    }
+
+    #[test]
+    fn streak_same_day_only() {
+        let mut buf = String::new();
+
+        add_solved(0, 4, &mut buf);
+        add_solved(1, 4, &mut buf);
+        add_solved(2, 4, &mut buf);
+        add_solved(3, 4, &mut buf);
+
+        let mut save_states = load_save_states(&buf).unwrap();
+
+        save_states.get_mut(&0).unwrap().set_played_on(Some(0));
+        // Puzzle 1 was played late from the archive
+        save_states.get_mut(&1).unwrap().set_played_on(Some(3));
+        save_states.get_mut(&3).unwrap().set_played_on(Some(3));
+
+        let statistics = Statistics::new(
+            &save_states,
+            3,
+            &StreakRules::default(),
+        );
+        assert_eq!(statistics.best_streak(), 4);
+        assert_eq!(statistics.current_streak(), 4);
+
+        let statistics = Statistics::new(
+            &save_states,
+            3,
+            &StreakRules { grace_days: 0, same_day_only: true },
+        );
+        // Puzzle 2 has no day recorded so it is assumed to be played
+        // on the day
+        assert_eq!(statistics.best_streak(), 2);
+        assert_eq!(statistics.current_streak(), 2);
+        // The puzzle still counts for the other statistics
+        assert_eq!(statistics.total_stars(), 16);
+    }
+
+    #[test]
+    fn period_statistics() {
+        let mut buf = String::new();
+
+        // Monday 2023-08-07
+        add_solved(0, 5, &mut buf);
+        // Tuesday
+        add_solved(1, 5, &mut buf);
+        // Wednesday
+        add_solved(2, 5, &mut buf);
+        add_fail(3, &mut buf);
+        add_solved(4, 5, &mut buf);
+        // Not finished so it doesn’t count
+        add_unfinished(7, &mut buf);
+        // Thursday 2023-08-31
+        add_solved(24, 5, &mut buf);
+        // Friday 2023-09-01
+        add_solved(25, 3, &mut buf);
+        // Monday 2023-09-04
+        add_solved(28, 5, &mut buf);
+
+        let statistics = statistics_for(&load_save_states(&buf).unwrap());
+
+        assert_eq!(statistics.best_perfect_run(), 3);
+
+        let overall = statistics.overall();
+        assert_eq!(overall.n_finished(), 8);
+        assert_eq!(overall.n_solved(), 7);
+        assert_eq!(overall.win_rate(), Some(88));
+        assert_eq!(overall.average_stars(), Some(33.0 / 8.0));
+        assert_eq!(
+            overall.average_swaps_used(),
+            Some((10.0 * 6.0 + 12.0 + 15.0) / 8.0),
+        );
+
+        let months = statistics.months();
+        assert_eq!(months.len(), 2);
+        assert_eq!((months[0].year, months[0].month), (2023, 8));
+        assert_eq!(months[0].statistics.n_finished(), 6);
+        assert_eq!(months[0].statistics.n_solved(), 5);
+        assert_eq!((months[1].year, months[1].month), (2023, 9));
+        assert_eq!(months[1].statistics.n_finished(), 2);
+        assert_eq!(months[1].statistics.average_stars(), Some(4.0));
+
+        let weekdays = statistics.weekdays();
+        assert_eq!(weekdays[0].n_finished(), 2);
+        assert_eq!(weekdays[0].n_solved(), 2);
+        assert_eq!(weekdays[3].n_finished(), 2);
+        assert_eq!(weekdays[3].n_solved(), 1);
+        assert_eq!(weekdays[3].win_rate(), Some(50));
+        assert_eq!(weekdays[4].average_stars(), Some(4.0));
+        assert_eq!(weekdays[6].n_finished(), 0);
+        assert_eq!(weekdays[6].average_stars(), None);
+        assert_eq!(weekdays[6].win_rate(), None);
+    }
+
+    #[test]
+    fn statistics_times() {
+        let mut buf = String::new();
+
+        add_solved(3, 4, &mut buf);
+        add_solved(4, 4, &mut buf);
+        buf.push_str(":t100");
+        add_solved(5, 5, &mut buf);
+        buf.push_str(":t51");
+        // Failed puzzles don’t count towards the times
+        add_fail(6, &mut buf);
+        buf.push_str(":t10");
+
+        let statistics = statistics_for(&load_save_states(&buf).unwrap());
+
+        assert_eq!(statistics.best_time(), Some(51));
+        assert_eq!(statistics.average_time(), Some(76));
+
+        let mut buf = String::new();
+        add_solved(3, 4, &mut buf);
+        let statistics = statistics_for(&load_save_states(&buf).unwrap());
+        assert_eq!(statistics.best_time(), None);
+        assert_eq!(statistics.average_time(), None);
+    }
+
     #[test]
     fn statistics_gap() {
         let mut buf = String::new();
@@ -596,7 +1418,7 @@ mod test {
 
         add_solved(7, 2, &mut buf);
 
-        let statistics = Statistics::new(&load_save_states(&buf).unwrap());
+        let statistics = statistics_for(&load_save_states(&buf).unwrap());
 
         assert_eq!(statistics.best_streak(), 2);
         assert_eq!(statistics.current_streak(), 1);
@@ -613,7 +1435,7 @@ mod test {
     #[test]
     fn share_text_solved() {
         let save_states = make_save_states_for_stars(0);
-        let statistics = Statistics::new(&save_states);
+        let statistics = statistics_for(&save_states);
         let save_state = save_states.values().next().unwrap();
 
         assert_eq!(
@@ -627,11 +1449,11 @@ mod test {
              \n\
              🔥 gajnvico: 1\n\
              https://vaflo.net",
-            &statistics.share_text(4, &save_state)
+            &statistics.share_text(4, &save_state, &Settings::default())
         );
 
         let save_states = make_save_states_for_stars(1);
-        let statistics = Statistics::new(&save_states);
+        let statistics = statistics_for(&save_states);
         let save_state = save_states.values().next().unwrap();
 
         assert_eq!(
@@ -645,11 +1467,11 @@ mod test {
              \n\
              🔥 gajnvico: 1\n\
              https://vaflo.net",
-            &statistics.share_text(4, &save_state)
+            &statistics.share_text(4, &save_state, &Settings::default())
         );
 
         let save_states = make_save_states_for_stars(2);
-        let statistics = Statistics::new(&save_states);
+        let statistics = statistics_for(&save_states);
         let save_state = save_states.values().next().unwrap();
 
         assert_eq!(
@@ -663,11 +1485,11 @@ mod test {
              \n\
              🔥 gajnvico: 1\n\
              https://vaflo.net",
-            &statistics.share_text(4, &save_state)
+            &statistics.share_text(4, &save_state, &Settings::default())
         );
 
         let save_states = make_save_states_for_stars(3);
-        let statistics = Statistics::new(&save_states);
+        let statistics = statistics_for(&save_states);
         let save_state = save_states.values().next().unwrap();
 
         assert_eq!(
@@ -681,11 +1503,11 @@ mod test {
              \n\
              🔥 gajnvico: 1\n\
              https://vaflo.net",
-            &statistics.share_text(4, &save_state)
+            &statistics.share_text(4, &save_state, &Settings::default())
         );
 
         let save_states = make_save_states_for_stars(4);
-        let statistics = Statistics::new(&save_states);
+        let statistics = statistics_for(&save_states);
         let save_state = save_states.values().next().unwrap();
 
         assert_eq!(
@@ -699,11 +1521,11 @@ mod test {
              \n\
              🔥 gajnvico: 1\n\
              https://vaflo.net",
-            &statistics.share_text(4, &save_state)
+            &statistics.share_text(4, &save_state, &Settings::default())
         );
 
         let save_states = make_save_states_for_stars(5);
-        let statistics = Statistics::new(&save_states);
+        let statistics = statistics_for(&save_states);
         let save_state = save_states.values().next().unwrap();
 
         assert_eq!(
@@ -717,7 +1539,7 @@ mod test {
              \n\
              🔥 gajnvico: 1\n\
              https://vaflo.net",
-            &statistics.share_text(4, &save_state)
+            &statistics.share_text(4, &save_state, &Settings::default())
         );
     }
 
@@ -727,7 +1549,7 @@ mod test {
         add_fail(4, &mut buf);
         let save_states = load_save_states(&buf).unwrap();
 
-        let statistics = Statistics::new(&save_states);
+        let statistics = statistics_for(&save_states);
         let save_state = save_states.values().next().unwrap();
 
         assert_eq!(
@@ -741,8 +1563,206 @@ mod test {
              \n\
              💔 gajnvico: 0\n\
              https://vaflo.net",
-            &statistics.share_text(4, &save_state)
+            &statistics.share_text(4, &save_state, &Settings::default())
+        );
+    }
+
+    #[test]
+    fn share_text_palettes() {
+        let mut buf = String::new();
+        add_fail(4, &mut buf);
+        let save_states = load_save_states(&buf).unwrap();
+
+        let statistics = statistics_for(&save_states);
+        let save_state = save_states.values().next().unwrap();
+
+        assert_eq!(
+            "#vaflo5 X/5\n\
+             \n\
+             🟧🟧⬛⬛🟧\n\
+             🟧⬜⬛⬜⬛\n\
+             ⬛⬛⬛⬛🟧\n\
+             ⬛⬜⬛⬜🟧\n\
+             ⬛⬛🟧⬛⬛\n\
+             \n\
+             💔 gajnvico: 0\n\
+             https://vaflo.net",
+            &statistics.share_text(
+                4,
+                &save_state,
+                &Settings {
+                    palette: Palette::ColorBlind,
+                    ..Default::default()
+                },
+            ),
+        );
+
+        let share_text = statistics.share_text(
+            4,
+            &save_state,
+            &Settings {
+                palette: Palette::HighContrast,
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(share_text.chars().filter(|&ch| ch == '🟦').count(), 7);
+        assert!(!share_text.contains('🟩'));
+    }
+
+    #[test]
+    fn share_text_hints() {
+        let mut buf = String::new();
+        add_puzzle(
+            4,
+            "MORSAUUKROLASDOOURSOJ\
+             arcdnhfjvlmewpxbukoty",
+            3,
+            &mut buf,
+        );
+        buf.push_str(":h2");
+        let save_states = load_save_states(&buf).unwrap();
+
+        let statistics = statistics_for(&save_states);
+        let save_state = save_states.values().next().unwrap();
+
+        let share_text = statistics.share_text(
+            4,
+            &save_state,
+            &Settings::default(),
+        );
+
+        assert_eq!(
+            share_text.lines().next().unwrap(),
+            "#vaflo5 3/5 💡 💡",
+        );
+    }
+
+    #[test]
+    fn share_text_hard_mode() {
+        let mut buf = String::new();
+        add_solved(4, 5, &mut buf);
+        let mut save_states = load_save_states(&buf).unwrap();
+
+        let statistics = statistics_for(&save_states);
+        let save_state = save_states.get_mut(&4).unwrap();
+
+        let share_text = statistics.share_text(
+            4,
+            &save_state,
+            &Settings::default(),
         );
+        assert_eq!(share_text.lines().next().unwrap(), "#vaflo5 5/5");
+
+        save_state.set_hard_mode(true);
+
+        let share_text = statistics.share_text(
+            4,
+            &save_state,
+            &Settings::default(),
+        );
+        assert_eq!(share_text.lines().next().unwrap(), "#vaflo5 5/5*");
+    }
+
+    #[test]
+    fn share_text_template() {
+        let mut buf = String::new();
+        add_solved(4, 4, &mut buf);
+        buf.push_str(":t83");
+        let save_states = load_save_states(&buf).unwrap();
+
+        let statistics = statistics_for(&save_states);
+        let save_state = save_states.values().next().unwrap();
+
+        let settings = Settings { timer: true, ..Settings::default() };
+
+        let template = "hashtag=#gaufre\n\
+                        streak=série\n\
+                        url=https://jeudegaufre.fr\n\
+                        stars=0"
+            .parse::<ShareTemplate>()
+            .unwrap();
+
+        assert_eq!(
+            "#gaufre5 4/5\n\
+             \n\
+             🟩🟩🟩🟩🟩\n\
+             🟩⬜🟩⬜🟩\n\
+             🟩🟩🟩🟩🟩\n\
+             🟩⬜🟩⬜🟩\n\
+             🟩🟩🟩🟩🟩\n\
+             \n\
+             🔥 série: 1\n\
+             ⏱️ 1:23\n\
+             https://jeudegaufre.fr",
+            &statistics.share_text_with_template(
+                4,
+                &save_state,
+                &settings,
+                &template,
+            ),
+        );
+
+        let template = "grid=0\n\
+                        time=0\n\
+                        url="
+            .parse::<ShareTemplate>()
+            .unwrap();
+
+        assert_eq!(
+            "#vaflo5 4/5\n\
+             \n\
+             🔥 gajnvico: 1",
+            &statistics.share_text_with_template(
+                4,
+                &save_state,
+                &settings,
+                &template,
+            ),
+        );
+
+        // The default template gives the same as share_text
+        assert_eq!(
+            statistics.share_text(4, &save_state, &settings),
+            statistics.share_text_with_template(
+                4,
+                &save_state,
+                &settings,
+                &ShareTemplate::default(),
+            ),
+        );
+    }
+
+    #[test]
+    fn share_text_time() {
+        let mut buf = String::new();
+        add_solved(4, 5, &mut buf);
+        buf.push_str(":t83");
+        let save_states = load_save_states(&buf).unwrap();
+
+        let statistics = statistics_for(&save_states);
+        let save_state = save_states.values().next().unwrap();
+
+        let share_text = statistics.share_text(
+            4,
+            &save_state,
+            &Settings::default(),
+        );
+        assert!(!share_text.contains('⏱'));
+
+        let share_text = statistics.share_text(
+            4,
+            &save_state,
+            &Settings {
+                timer: true,
+                ..Default::default()
+            },
+        );
+        assert!(share_text.ends_with(
+            "🔥 gajnvico: 1\n\
+             ⏱️ 1:23\n\
+             https://vaflo.net"
+        ));
     }
 
     #[test]
@@ -754,12 +1774,16 @@ mod test {
 
         let mut buf = String::new();
         add_solved(4, 4, &mut buf);
-        let statistics = Statistics::new(&load_save_states(&buf).unwrap());
+        let statistics = statistics_for(&load_save_states(&buf).unwrap());
 
         for n_stars in 0..=MAXIMUM_STARS {
             let save_state = SaveState::new(grid.clone(), n_stars);
 
-            let share_text = statistics.share_text(1, &save_state);
+            let share_text = statistics.share_text(
+                1,
+                &save_state,
+                &Settings::default(),
+            );
             let n_stars_in_share_text = share_text.chars()
                 .filter(|&ch| ch == '⭐')
                 .count();
@@ -0,0 +1,241 @@
+// Vaflo – A word game in Esperanto
+// Copyright (C) 2025  Neil Roberts
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+// The puzzles file has a puzzle on each line. Each line can
+// optionally start with a date in the form YYYY-MM-DD followed by a
+// space to say which day the puzzle is for. Otherwise the puzzle is
+// for the day after the puzzle on the previous line, or the day of
+// the first puzzle if it is the first line. If a later line is for
+// the same day as an earlier line then it replaces it. Empty lines
+// and lines starting with ‘#’ are ignored.
+
+use std::fmt;
+use std::collections::BTreeMap;
+use super::grid::{self, Grid};
+use super::date::{self, Date};
+
+#[derive(Clone, Debug)]
+pub struct ScheduledPuzzle {
+    pub date: Date,
+    pub grid: Grid,
+}
+
+#[derive(Debug)]
+pub enum ParseError {
+    InvalidDate(usize, date::ParseError),
+    DateTooEarly(usize, Date),
+    InvalidGrid(usize, grid::GridParseError),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::InvalidDate(line_num, e) => {
+                write!(f, "line {}: {}", line_num, e)
+            },
+            ParseError::DateTooEarly(line_num, date) => {
+                write!(
+                    f,
+                    "line {}: {} is before the first puzzle",
+                    line_num,
+                    date,
+                )
+            },
+            ParseError::InvalidGrid(line_num, e) => {
+                write!(f, "line {}: {}", line_num, e)
+            },
+        }
+    }
+}
+
+impl ScheduledPuzzle {
+    pub fn puzzle_number(&self) -> usize {
+        // The parser makes sure the date isn’t too early
+        self.date.puzzle_number().unwrap()
+    }
+}
+
+// Parses the puzzles file. The returned puzzles are sorted by date
+// and there is at most one puzzle for each date.
+pub fn parse(s: &str) -> Result<Vec<ScheduledPuzzle>, ParseError> {
+    let mut puzzles = BTreeMap::new();
+    let mut next_date = Date::first_puzzle();
+
+    for (line_num, line) in s.lines().enumerate() {
+        let line_num = line_num + 1;
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (date, grid) = match line.split_once(' ') {
+            Some((date, grid)) => {
+                let date = date.parse::<Date>().map_err(|e| {
+                    ParseError::InvalidDate(line_num, e)
+                })?;
+
+                if date.puzzle_number().is_none() {
+                    return Err(ParseError::DateTooEarly(line_num, date));
+                }
+
+                (date, grid.trim_start())
+            },
+            None => (next_date, line),
+        };
+
+        let grid = grid.parse::<Grid>().map_err(|e| {
+            ParseError::InvalidGrid(line_num, e)
+        })?;
+
+        puzzles.insert(date, grid);
+
+        next_date = date.add_days(1);
+    }
+
+    Ok(puzzles.into_iter()
+       .map(|(date, grid)| ScheduledPuzzle { date, grid })
+       .collect())
+}
+
+// Converts the puzzles back to the format of the puzzles file. The
+// date is only written if it isn’t the day after the previous
+// puzzle so that a file with a puzzle for every day stays the same.
+pub fn to_string(puzzles: &[ScheduledPuzzle]) -> String {
+    let mut result = String::new();
+    let mut next_date = Date::first_puzzle();
+
+    for puzzle in puzzles.iter() {
+        if puzzle.date != next_date {
+            result.push_str(&format!("{} ", puzzle.date));
+        }
+
+        result.push_str(&format!("{}\n", puzzle.grid));
+
+        next_date = puzzle.date.add_days(1);
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const PUZZLE_A: &str = "ABCDEFHJKLMNOPRTUVWXY\
+                            abcdefhjklmnoprtuvwxy";
+    const PUZZLE_B: &str = "ABCDEFHJKLMNOPRTUVWXY\
+                            bacdefhjklmnoprtuvwxy";
+    const PUZZLE_C: &str = "MORSAUUKROLASDOOURSOJ\
+                            arcdnhfjvlmewpxbukoty";
+
+    fn grid_strings(puzzles: &[ScheduledPuzzle]) -> Vec<(String, String)> {
+        puzzles.iter()
+            .map(|puzzle| (puzzle.date.to_string(), puzzle.grid.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn implicit_dates() {
+        let puzzles = parse(&format!(
+            "{}\n{}\n\n# comment\n{}\n",
+            PUZZLE_A,
+            PUZZLE_B,
+            PUZZLE_C,
+        )).unwrap();
+
+        assert_eq!(
+            grid_strings(&puzzles),
+            vec![
+                ("2023-08-07".to_string(), PUZZLE_A.to_string()),
+                ("2023-08-08".to_string(), PUZZLE_B.to_string()),
+                ("2023-08-09".to_string(), PUZZLE_C.to_string()),
+            ],
+        );
+
+        assert_eq!(puzzles[2].puzzle_number(), 2);
+    }
+
+    #[test]
+    fn gaps_and_replacements() {
+        let source = format!(
+            "{}\n\
+             2023-08-10 {}\n\
+             {}\n\
+             2023-08-07 {}\n",
+            PUZZLE_A,
+            PUZZLE_B,
+            PUZZLE_C,
+            PUZZLE_C,
+        );
+
+        let puzzles = parse(&source).unwrap();
+
+        assert_eq!(
+            grid_strings(&puzzles),
+            vec![
+                // The last line replaces the first one
+                ("2023-08-07".to_string(), PUZZLE_C.to_string()),
+                ("2023-08-10".to_string(), PUZZLE_B.to_string()),
+                ("2023-08-11".to_string(), PUZZLE_C.to_string()),
+            ],
+        );
+
+        assert_eq!(
+            puzzles.iter()
+                .map(ScheduledPuzzle::puzzle_number)
+                .collect::<Vec<_>>(),
+            vec![0, 3, 4],
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let source = format!(
+            "{}\n\
+             {}\n\
+             2023-09-01 {}\n\
+             {}\n",
+            PUZZLE_A,
+            PUZZLE_B,
+            PUZZLE_C,
+            PUZZLE_A,
+        );
+
+        let puzzles = parse(&source).unwrap();
+        assert_eq!(to_string(&puzzles), source);
+
+        let source = format!("2024-01-01 {}\n", PUZZLE_A);
+        assert_eq!(to_string(&parse(&source).unwrap()), source);
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(
+            parse(&format!("{}\n2023-02-30 {}", PUZZLE_A, PUZZLE_B)),
+            Err(ParseError::InvalidDate(2, _)),
+        ));
+        assert!(matches!(
+            parse(&format!("2023-08-06 {}", PUZZLE_A)),
+            Err(ParseError::DateTooEarly(1, _)),
+        ));
+        assert!(matches!(
+            parse(&format!("{}\n\nABC", PUZZLE_A)),
+            Err(ParseError::InvalidGrid(3, _)),
+        ));
+        assert!(parse("").unwrap().is_empty());
+    }
+}
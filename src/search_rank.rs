@@ -0,0 +1,276 @@
+// Vaflo – A word game in Esperanto
+// Copyright (C) 2025  Neil Roberts
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+// Scores the results of a pattern search in the editor so that the
+// most useful words are shown first. The score is from 0 to 100 and
+// combines how long ago the word was last used, how common the word
+// is and how many words would still fit in the empty slots that cross
+// it.
+
+use std::fmt;
+use std::collections::HashMap;
+use super::dictionary::Dictionary;
+use super::word_search;
+
+// Number of puzzles since a word was last used after which it counts
+// as completely fresh
+const FRESH_AGE: usize = 365;
+// Number of words that can fit in a crossing slot that counts as
+// plenty of choice
+const PLENTY_OF_OPTIONS: usize = 20;
+
+const AGE_WEIGHT: f32 = 2.0;
+const OPTIONS_WEIGHT: f32 = 2.0;
+const FREQUENCY_WEIGHT: f32 = 1.0;
+
+#[derive(Debug)]
+pub enum ParseError {
+    InvalidLine(usize),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::InvalidLine(line_num) => {
+                write!(f, "line {}: expected a word and a count", line_num)
+            },
+        }
+    }
+}
+
+// Word frequencies loaded from a file with a line for each word in
+// the form ‘word count’. Empty lines and lines starting with ‘#’ are
+// ignored.
+#[derive(Debug, Default)]
+pub struct Frequencies {
+    words: HashMap<String, u32>,
+    max_count: u32,
+}
+
+impl Frequencies {
+    // Returns how common the word is from 0 to 1 on a log scale
+    // relative to the most common word
+    pub fn fraction(&self, word: &str) -> f32 {
+        let count = self.words.get(&word.to_lowercase())
+            .copied()
+            .unwrap_or(0);
+
+        if self.max_count == 0 {
+            0.0
+        } else {
+            (count as f32).ln_1p() / (self.max_count as f32).ln_1p()
+        }
+    }
+}
+
+pub fn parse_frequencies(s: &str) -> Result<Frequencies, ParseError> {
+    let mut frequencies = Frequencies::default();
+
+    for (line_num, line) in s.lines().enumerate() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let Some((word, count)) = line.split_once(char::is_whitespace)
+            .and_then(|(word, count)| {
+                Some((word, count.trim().parse::<u32>().ok()?))
+            })
+        else {
+            return Err(ParseError::InvalidLine(line_num + 1));
+        };
+
+        frequencies.max_count = frequencies.max_count.max(count);
+        frequencies.words.insert(word.to_lowercase(), count);
+    }
+
+    Ok(frequencies)
+}
+
+#[derive(Debug, Default)]
+pub struct Factors {
+    // Number of puzzles between the current one and the last one to
+    // use the word, or None if it has never been used
+    pub age: Option<usize>,
+    // How common the word is from 0 to 1, or None if no frequencies
+    // were loaded
+    pub frequency: Option<f32>,
+    // The smallest number of words that would still fit in one of
+    // the empty slots crossing the word, or None if none of the
+    // crossing slots are empty
+    pub crossing_options: Option<usize>,
+}
+
+pub fn score(factors: &Factors) -> u32 {
+    // A word that makes one of the crossing slots impossible is no
+    // use at all
+    if factors.crossing_options == Some(0) {
+        return 0;
+    }
+
+    let age = factors.age.unwrap_or(FRESH_AGE).min(FRESH_AGE);
+    let options = factors.crossing_options
+        .unwrap_or(PLENTY_OF_OPTIONS)
+        .min(PLENTY_OF_OPTIONS);
+
+    let mut total = age as f32 / FRESH_AGE as f32 * AGE_WEIGHT
+        + options as f32 / PLENTY_OF_OPTIONS as f32 * OPTIONS_WEIGHT;
+    let mut total_weight = AGE_WEIGHT + OPTIONS_WEIGHT;
+
+    if let Some(frequency) = factors.frequency {
+        total += frequency * FREQUENCY_WEIGHT;
+        total_weight += FREQUENCY_WEIGHT;
+    }
+
+    (total / total_weight * 100.0).round() as u32
+}
+
+// Counts the dictionary words that match the pattern of a crossing
+// slot, grouped by the letter they would put at `index`, which is
+// where the crossing slot meets the word being searched for
+pub fn crossing_letter_counts(
+    pattern: &str,
+    index: usize,
+    dictionary: &Dictionary,
+) -> HashMap<char, usize> {
+    let pattern = pattern.chars()
+        .enumerate()
+        .map(|(i, ch)| if i == index { '.' } else { ch })
+        .collect::<String>();
+
+    let mut counts = HashMap::new();
+
+    for word in word_search::search(&pattern, dictionary) {
+        if let Some(ch) = word.chars().nth(index) {
+            *counts.entry(ch).or_insert(0) += 1;
+        }
+    }
+
+    counts
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RankedWord {
+    pub word: String,
+    pub score: u32,
+}
+
+// Sorts the words so that the highest scores come first. Words with
+// the same score stay in the same order.
+pub fn sort(words: &mut [RankedWord]) {
+    words.sort_by_key(|word| std::cmp::Reverse(word.score));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use super::super::dictionary::test_data;
+
+    #[test]
+    fn frequencies() {
+        let frequencies = parse_frequencies(
+            "# comment\n\
+             kato 1000\n\
+             \n\
+             HUNDO 10\n"
+        ).unwrap();
+
+        assert_eq!(frequencies.fraction("KATO"), 1.0);
+        assert!(frequencies.fraction("hundo") > 0.0);
+        assert!(frequencies.fraction("hundo") < 0.5);
+        assert_eq!(frequencies.fraction("muso"), 0.0);
+
+        assert!(matches!(
+            parse_frequencies("kato 3\nhundo"),
+            Err(ParseError::InvalidLine(2)),
+        ));
+        assert!(matches!(
+            parse_frequencies("kato tri"),
+            Err(ParseError::InvalidLine(1)),
+        ));
+    }
+
+    #[test]
+    fn scores() {
+        assert_eq!(score(&Factors::default()), 100);
+
+        assert_eq!(
+            score(&Factors { age: Some(0), ..Default::default() }),
+            50,
+        );
+
+        assert_eq!(
+            score(&Factors {
+                age: Some(FRESH_AGE * 2),
+                crossing_options: Some(PLENTY_OF_OPTIONS / 2),
+                frequency: None,
+            }),
+            75,
+        );
+
+        assert_eq!(
+            score(&Factors {
+                age: None,
+                crossing_options: None,
+                frequency: Some(0.0),
+            }),
+            80,
+        );
+
+        assert_eq!(
+            score(&Factors {
+                crossing_options: Some(0),
+                ..Default::default()
+            }),
+            0,
+        );
+    }
+
+    #[test]
+    fn crossing_counts() {
+        let dictionary = test_data::search_words();
+
+        let counts = crossing_letter_counts("....o", 0, &dictionary);
+
+        assert_eq!(counts.len(), 3);
+        assert_eq!(counts.get(&'e'), Some(&1));
+        assert_eq!(counts.get(&'h'), Some(&1));
+        assert_eq!(counts.get(&'n'), Some(&1));
+
+        // The letter at the index is ignored
+        let counts = crossing_letter_counts("xa...", 0, &dictionary);
+
+        assert_eq!(counts.get(&'h'), Some(&1));
+        assert_eq!(counts.get(&'r'), Some(&1));
+        assert_eq!(counts.get(&'e'), None);
+    }
+
+    #[test]
+    fn sorting() {
+        let mut words = [("a", 10), ("b", 50), ("c", 10), ("d", 90)]
+            .into_iter()
+            .map(|(word, score)| RankedWord { word: word.to_string(), score })
+            .collect::<Vec<_>>();
+
+        sort(&mut words);
+
+        assert_eq!(
+            words.iter().map(|word| word.word.as_str()).collect::<String>(),
+            "dbac",
+        );
+    }
+}
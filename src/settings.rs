@@ -0,0 +1,304 @@
+// Vaflo – A word game in Esperanto
+// Copyright (C) 2025  Neil Roberts
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::fmt;
+use std::str::FromStr;
+use super::grid::PuzzleSquareState;
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Palette {
+    #[default]
+    Normal,
+    ColorBlind,
+    HighContrast,
+}
+
+// Rules for deciding whether a streak continues
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StreakRules {
+    // Number of days that can be missed without breaking the streak
+    pub grace_days: u32,
+    // If true, puzzles solved on a later day from the archive don’t
+    // count towards the streak
+    pub same_day_only: bool,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Settings {
+    pub palette: Palette,
+    // Whether to draw a symbol on the correct and wrong-position
+    // letters so that they can be told apart without the colour
+    pub markers: bool,
+    // Whether to show the time taken while playing and in the share text
+    pub timer: bool,
+    // Whether new puzzles should be played without the yellow squares
+    pub hard_mode: bool,
+    pub streak_rules: StreakRules,
+}
+
+#[derive(Debug)]
+pub enum ParseError {
+    MissingEquals,
+    UnknownKey(String),
+    InvalidPalette,
+    InvalidBool,
+    InvalidNumber,
+}
+
+static PALETTE_NAMES: [(Palette, &str); 3] = [
+    (Palette::Normal, "normal"),
+    (Palette::ColorBlind, "color-blind"),
+    (Palette::HighContrast, "high-contrast"),
+];
+
+impl Palette {
+    pub fn name(self) -> &'static str {
+        PALETTE_NAMES.iter()
+            .find(|&&(palette, _)| palette == self)
+            .unwrap()
+            .1
+    }
+
+    // Extra class to add to the letters so that the stylesheet can
+    // pick the colours
+    pub fn class_name(self) -> Option<&'static str> {
+        match self {
+            Palette::Normal => None,
+            Palette::ColorBlind => Some("palette-color-blind"),
+            Palette::HighContrast => Some("palette-high-contrast"),
+        }
+    }
+
+    // The emoji used for a square in the share text. Only the colour
+    // of the correct squares changes with the palette because the
+    // share text doesn’t tell the other two states apart.
+    pub fn share_emoji(self, state: PuzzleSquareState) -> char {
+        match state {
+            PuzzleSquareState::Correct => match self {
+                Palette::Normal => '🟩',
+                Palette::ColorBlind => '🟧',
+                Palette::HighContrast => '🟦',
+            },
+            PuzzleSquareState::WrongPosition
+                | PuzzleSquareState::Wrong => '⬛',
+        }
+    }
+}
+
+impl FromStr for Palette {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Palette, ParseError> {
+        PALETTE_NAMES.iter()
+            .find(|&&(_, name)| name == s)
+            .map(|&(palette, _)| palette)
+            .ok_or(ParseError::InvalidPalette)
+    }
+}
+
+impl fmt::Display for Palette {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+pub fn parse_bool(s: &str) -> Result<bool, ParseError> {
+    match s {
+        "0" => Ok(false),
+        "1" => Ok(true),
+        _ => Err(ParseError::InvalidBool),
+    }
+}
+
+impl FromStr for Settings {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Settings, ParseError> {
+        let mut settings = Settings::default();
+
+        for setting in s.split(',').filter(|s| !s.is_empty()) {
+            let Some((key, value)) = setting.split_once('=')
+            else {
+                return Err(ParseError::MissingEquals);
+            };
+
+            match key {
+                "palette" => settings.palette = value.parse()?,
+                "markers" => settings.markers = parse_bool(value)?,
+                "timer" => settings.timer = parse_bool(value)?,
+                "hard" => settings.hard_mode = parse_bool(value)?,
+                "grace" => {
+                    let Ok(grace_days) = value.parse::<u32>()
+                    else {
+                        return Err(ParseError::InvalidNumber);
+                    };
+                    settings.streak_rules.grace_days = grace_days;
+                },
+                "sameday" => {
+                    settings.streak_rules.same_day_only = parse_bool(value)?;
+                },
+                // Ignore unknown keys so that settings saved by a
+                // different version of the game don’t reset the rest
+                _ => (),
+            }
+        }
+
+        Ok(settings)
+    }
+}
+
+impl fmt::Display for Settings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "palette={},markers={},timer={},hard={},grace={},sameday={}",
+            self.palette,
+            self.markers as u8,
+            self.timer as u8,
+            self.hard_mode as u8,
+            self.streak_rules.grace_days,
+            self.streak_rules.same_day_only as u8,
+        )
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::MissingEquals => write!(f, "missing equals sign"),
+            ParseError::UnknownKey(key) => write!(f, "unknown key “{}”", key),
+            ParseError::InvalidPalette => write!(f, "invalid palette"),
+            ParseError::InvalidBool => write!(f, "invalid boolean value"),
+            ParseError::InvalidNumber => write!(f, "invalid number"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let settings = "".parse::<Settings>().unwrap();
+        assert_eq!(settings.palette, Palette::Normal);
+        assert!(!settings.markers);
+
+        let settings = "palette=high-contrast,markers=1"
+            .parse::<Settings>()
+            .unwrap();
+        assert_eq!(settings.palette, Palette::HighContrast);
+        assert!(settings.markers);
+        assert!(!settings.timer);
+
+        let settings = "timer=1".parse::<Settings>().unwrap();
+        assert!(settings.timer);
+        assert!(!settings.hard_mode);
+
+        let settings = "hard=1".parse::<Settings>().unwrap();
+        assert!(settings.hard_mode);
+        assert_eq!(settings.streak_rules, StreakRules::default());
+
+        let settings = "grace=2,sameday=1".parse::<Settings>().unwrap();
+        assert_eq!(settings.streak_rules.grace_days, 2);
+        assert!(settings.streak_rules.same_day_only);
+
+        let settings = "markers=0,palette=color-blind"
+            .parse::<Settings>()
+            .unwrap();
+        assert_eq!(settings.palette, Palette::ColorBlind);
+        assert!(!settings.markers);
+    }
+
+    #[test]
+    fn bad_settings() {
+        assert!(matches!(
+            "palette".parse::<Settings>(),
+            Err(ParseError::MissingEquals),
+        ));
+        assert!(matches!(
+            "palette=purple".parse::<Settings>(),
+            Err(ParseError::InvalidPalette),
+        ));
+        assert!(matches!(
+            "markers=yes".parse::<Settings>(),
+            Err(ParseError::InvalidBool),
+        ));
+        assert!(matches!(
+            "grace=-1".parse::<Settings>(),
+            Err(ParseError::InvalidNumber),
+        ));
+    }
+
+    #[test]
+    fn unknown_key() {
+        let settings = "volume=11".parse::<Settings>().unwrap();
+        assert_eq!(settings.palette, Palette::Normal);
+
+        let settings = "timer=1,volume=11,palette=color-blind"
+            .parse::<Settings>()
+            .unwrap();
+        assert!(settings.timer);
+        assert_eq!(settings.palette, Palette::ColorBlind);
+    }
+
+    #[test]
+    fn class_names() {
+        assert_eq!(Palette::Normal.class_name(), None);
+        assert_eq!(
+            Palette::ColorBlind.class_name(),
+            Some("palette-color-blind"),
+        );
+        assert_eq!(
+            Palette::HighContrast.class_name(),
+            Some("palette-high-contrast"),
+        );
+    }
+
+    #[test]
+    fn display() {
+        for (palette, _) in PALETTE_NAMES.iter() {
+            for markers in [false, true] {
+                for timer in [false, true] {
+                    for hard_mode in [false, true] {
+                        let settings = Settings {
+                            palette: *palette,
+                            markers,
+                            timer,
+                            hard_mode,
+                            streak_rules: StreakRules {
+                                grace_days: markers as u32 * 3,
+                                same_day_only: timer,
+                            },
+                        };
+                        let reparsed = settings.to_string()
+                            .parse::<Settings>()
+                            .unwrap();
+
+                        assert_eq!(reparsed.palette, settings.palette);
+                        assert_eq!(reparsed.markers, settings.markers);
+                        assert_eq!(reparsed.timer, settings.timer);
+                        assert_eq!(reparsed.hard_mode, settings.hard_mode);
+                        assert_eq!(
+                            reparsed.streak_rules,
+                            settings.streak_rules,
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
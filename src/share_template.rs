@@ -0,0 +1,137 @@
+// Vaflo – A word game in Esperanto
+// Copyright (C) 2025  Neil Roberts
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::str::FromStr;
+use super::settings::{self, ParseError};
+
+// The URL of the site that is added to the end of the share text
+pub const DEFAULT_URL: &str = "https://vaflo.net";
+const DEFAULT_HASHTAG: &str = "#vaflo";
+const DEFAULT_STREAK_LABEL: &str = "gajnvico";
+
+// Describes how the share text should look so that each site can
+// have its own wording
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShareTemplate {
+    // Added before the puzzle number on the first line
+    pub hashtag: String,
+    // The word used for the streak
+    pub streak_label: String,
+    // Added as the last line. If it’s empty then there is no URL.
+    pub url: String,
+    pub include_grid: bool,
+    // Whether to put stars in the grid for the swaps remaining
+    pub include_stars: bool,
+    // Whether the time can be included. It is also only added if the
+    // player has enabled the timer.
+    pub include_time: bool,
+}
+
+impl Default for ShareTemplate {
+    fn default() -> ShareTemplate {
+        ShareTemplate {
+            hashtag: DEFAULT_HASHTAG.to_string(),
+            streak_label: DEFAULT_STREAK_LABEL.to_string(),
+            url: DEFAULT_URL.to_string(),
+            include_grid: true,
+            include_stars: true,
+            include_time: true,
+        }
+    }
+}
+
+// The template is parsed from lines of the form “key=value”. Any keys
+// that aren’t given keep their default value.
+impl FromStr for ShareTemplate {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<ShareTemplate, ParseError> {
+        let mut template = ShareTemplate::default();
+
+        for line in s.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let Some((key, value)) = line.split_once('=')
+            else {
+                return Err(ParseError::MissingEquals);
+            };
+
+            let value = value.trim();
+
+            match key.trim() {
+                "hashtag" => template.hashtag = value.to_string(),
+                "streak" => template.streak_label = value.to_string(),
+                "url" => template.url = value.to_string(),
+                "grid" => template.include_grid = settings::parse_bool(value)?,
+                "stars" => {
+                    template.include_stars = settings::parse_bool(value)?;
+                },
+                "time" => template.include_time = settings::parse_bool(value)?,
+                key => return Err(ParseError::UnknownKey(key.to_string())),
+            }
+        }
+
+        Ok(template)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            "".parse::<ShareTemplate>().unwrap(),
+            ShareTemplate::default(),
+        );
+
+        let template = "hashtag=#gaufre\n\
+                        \n\
+                        streak = série \n\
+                        url=https://jeudegaufre.fr\n\
+                        grid=0\n\
+                        stars=0\n\
+                        time=0\n"
+            .parse::<ShareTemplate>()
+            .unwrap();
+
+        assert_eq!(&template.hashtag, "#gaufre");
+        assert_eq!(&template.streak_label, "série");
+        assert_eq!(&template.url, "https://jeudegaufre.fr");
+        assert!(!template.include_grid);
+        assert!(!template.include_stars);
+        assert!(!template.include_time);
+
+        let template = "url=".parse::<ShareTemplate>().unwrap();
+        assert!(template.url.is_empty());
+        assert_eq!(&template.hashtag, "#vaflo");
+    }
+
+    #[test]
+    fn bad_template() {
+        assert!(matches!(
+            "hashtag".parse::<ShareTemplate>(),
+            Err(ParseError::MissingEquals),
+        ));
+        assert!(matches!(
+            "grid=yes".parse::<ShareTemplate>(),
+            Err(ParseError::InvalidBool),
+        ));
+        assert!(matches!(
+            "colour=blue".parse::<ShareTemplate>(),
+            Err(ParseError::UnknownKey(key)) if key == "colour",
+        ));
+    }
+}
@@ -0,0 +1,191 @@
+// Vaflo – A word game in Esperanto
+// Copyright (C) 2025  Neil Roberts
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+mod grid;
+mod date;
+mod schedule;
+mod daily_puzzle;
+mod definitions;
+
+use std::process::ExitCode;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use clap::Parser;
+use schedule::ScheduledPuzzle;
+use definitions::Definitions;
+use date::Date;
+
+#[derive(Parser)]
+#[command(name = "split-puzzles")]
+struct Cli {
+    #[arg(short, long, value_name = "FILE")]
+    puzzles: Option<OsString>,
+    #[arg(short, long, value_name = "FILE",
+          help = "File of word definitions to include with the puzzles")]
+    definitions: Option<OsString>,
+    #[arg(short, long, value_name = "DIR",
+          help = "Directory of the website to write the puzzle files to")]
+    output: Option<OsString>,
+    #[arg(short, long, value_name = "DATE",
+          help = "Skip the puzzles before this date")]
+    from: Option<String>,
+    #[arg(short, long, value_name = "DATE",
+          help = "Skip the puzzles after this date")]
+    to: Option<String>,
+}
+
+fn parse_date_arg(arg: Option<String>) -> Result<Option<Date>, ()> {
+    arg.map(|arg| {
+        arg.parse::<Date>().map_err(|e| {
+            eprintln!("{}: {}", arg, e);
+        })
+    }).transpose()
+}
+
+fn load_puzzles(filename: &Path) -> Result<Vec<ScheduledPuzzle>, ()> {
+    let data = match std::fs::read_to_string(filename) {
+        Ok(data) => data,
+        Err(e) => {
+            eprintln!("{}: {}", filename.display(), e);
+            return Err(());
+        },
+    };
+
+    match schedule::parse(&data) {
+        Ok(puzzles) => Ok(puzzles),
+        Err(e) => {
+            eprintln!("{}: {}", filename.display(), e);
+            Err(())
+        },
+    }
+}
+
+// Loads the definitions file. The file is optional so it is not an
+// error if it doesn’t exist unless it was explicitly requested.
+fn load_definitions(
+    filename: &Path,
+    required: bool,
+) -> Result<Definitions, ()> {
+    let data = match std::fs::read_to_string(filename) {
+        Ok(data) => data,
+        Err(e) if !required && e.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(Definitions::new());
+        },
+        Err(e) => {
+            eprintln!("{}: {}", filename.display(), e);
+            return Err(());
+        },
+    };
+
+    definitions::parse(&data).map_err(|e| {
+        eprintln!("{}: {}", filename.display(), e);
+    })
+}
+
+// Removes the puzzle files left over from a previous run so that
+// puzzles that have since been moved or removed don’t linger
+fn remove_stale_puzzles(directory: &Path) -> Result<(), ()> {
+    let entries = std::fs::read_dir(directory).map_err(|e| {
+        eprintln!("{}: {}", directory.display(), e);
+    })?;
+
+    for entry in entries {
+        let path = entry.map_err(|e| {
+            eprintln!("{}: {}", directory.display(), e);
+        })?.path();
+
+        if path.extension().is_some_and(|ext| ext == "bin") {
+            std::fs::remove_file(&path).map_err(|e| {
+                eprintln!("{}: {}", path.display(), e);
+            })?;
+        }
+    }
+
+    Ok(())
+}
+
+fn write_puzzles(
+    output: &Path,
+    puzzles: &[ScheduledPuzzle],
+    definitions: &Definitions,
+) -> Result<(), ()> {
+    let directory = output.join(daily_puzzle::DIRECTORY);
+
+    if let Err(e) = std::fs::create_dir_all(&directory) {
+        eprintln!("{}: {}", directory.display(), e);
+        return Err(());
+    }
+
+    remove_stale_puzzles(&directory)?;
+
+    for puzzle in puzzles.iter() {
+        let filename = output.join(daily_puzzle::path(puzzle.date));
+        let data = daily_puzzle::encode(
+            puzzle.date,
+            &puzzle.grid,
+            definitions,
+        );
+
+        if let Err(e) = std::fs::write(&filename, data) {
+            eprintln!("{}: {}", filename.display(), e);
+            return Err(());
+        }
+    }
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let puzzles_filename = PathBuf::from(
+        cli.puzzles.unwrap_or("puzzles.txt".into())
+    );
+    let definitions_required = cli.definitions.is_some();
+    let definitions_filename = PathBuf::from(
+        cli.definitions.unwrap_or("definitions.txt".into())
+    );
+    let output = PathBuf::from(cli.output.unwrap_or("dist".into()));
+
+    let (Ok(from), Ok(to)) = (parse_date_arg(cli.from), parse_date_arg(cli.to))
+    else {
+        return ExitCode::FAILURE;
+    };
+
+    let Ok(mut puzzles) = load_puzzles(&puzzles_filename)
+    else {
+        return ExitCode::FAILURE;
+    };
+
+    puzzles.retain(|puzzle| {
+        from.is_none_or(|from| puzzle.date >= from)
+            && to.is_none_or(|to| puzzle.date <= to)
+    });
+
+    let Ok(definitions) = load_definitions(
+        &definitions_filename,
+        definitions_required,
+    )
+    else {
+        return ExitCode::FAILURE;
+    };
+
+    if write_puzzles(&output, &puzzles, &definitions).is_err() {
+        return ExitCode::FAILURE;
+    }
+
+    ExitCode::SUCCESS
+}
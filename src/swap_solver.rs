@@ -177,3 +177,172 @@ where
 {
     solve_cancellable(start, target, || false)
 }
+
+// Tries every path from item back to start that doesn’t visit an
+// item twice and returns the largest number of cycles that can be
+// made if the path is used as one of the cycles
+fn best_cycle_through(
+    edges: &mut [u8],
+    n_items: usize,
+    start: usize,
+    item: usize,
+    visited: &mut [bool],
+    cache: &mut HashMap<Vec<u8>, usize>,
+) -> usize {
+    if item == start {
+        return 1 + max_cycles(edges, n_items, cache);
+    }
+
+    let mut best = 0;
+
+    for next in 0..n_items {
+        let edge = item * n_items + next;
+
+        if edges[edge] == 0 || visited[next] {
+            continue;
+        }
+
+        edges[edge] -= 1;
+        visited[next] = true;
+
+        best = best.max(
+            best_cycle_through(edges, n_items, start, next, visited, cache)
+        );
+
+        visited[next] = false;
+        edges[edge] += 1;
+    }
+
+    best
+}
+
+// Finds the largest number of cycles that the edges can be split
+// into. edges is a matrix of the number of edges from each item to
+// each other item.
+fn max_cycles(
+    edges: &mut [u8],
+    n_items: usize,
+    cache: &mut HashMap<Vec<u8>, usize>,
+) -> usize {
+    let Some(first_edge) = edges.iter().position(|&count| count > 0)
+    else {
+        return 0;
+    };
+
+    if let Some(&result) = cache.get(edges) {
+        return result;
+    }
+
+    // The first edge has to be in one of the cycles. Splitting a
+    // cycle that visits an item twice would make more cycles, so
+    // only the cycles that don’t do that need to be tried.
+    let from = first_edge / n_items;
+    let to = first_edge % n_items;
+    let mut visited = vec![false; n_items];
+
+    visited[to] = true;
+    edges[first_edge] -= 1;
+
+    let best = best_cycle_through(
+        edges,
+        n_items,
+        from,
+        to,
+        &mut visited,
+        cache,
+    );
+
+    edges[first_edge] += 1;
+
+    cache.insert(edges.to_vec(), best);
+
+    best
+}
+
+// Works out the number of swaps needed to turn start into target
+// without finding the actual swaps. This is much faster than solve.
+// Each item in the wrong place can be seen as an edge from the item
+// that is there to the item that should be there. Any way of solving
+// it splits the edges into cycles and a cycle of n edges needs n-1
+// swaps, so the answer is the number of items in the wrong place
+// minus the largest number of cycles.
+pub fn minimum_swaps<T>(start: &[T], target: &[T]) -> Option<usize>
+where
+    T: Hash + Clone + Eq,
+{
+    assert_eq!(start.len(), target.len());
+
+    let mut item_numbers = HashMap::new();
+
+    for item in start.iter().chain(target.iter()) {
+        let next_number = item_numbers.len();
+        item_numbers.entry(item.clone()).or_insert(next_number);
+    }
+
+    let n_items = item_numbers.len();
+    let mut edges = vec![0u8; n_items * n_items];
+    let mut balance = vec![0isize; n_items];
+    let mut n_wrong = 0;
+
+    for (a, b) in start.iter().zip(target.iter()) {
+        if a != b {
+            let a = item_numbers[a];
+            let b = item_numbers[b];
+            edges[a * n_items + b] += 1;
+            balance[a] += 1;
+            balance[b] -= 1;
+            n_wrong += 1;
+        }
+    }
+
+    // If the items aren’t the same then there’s no solution
+    if balance.iter().any(|&b| b != 0) {
+        return None;
+    }
+
+    Some(n_wrong - max_cycles(&mut edges, n_items, &mut HashMap::new()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn simple_minimum_swaps() {
+        assert_eq!(minimum_swaps(b"abc", b"abc"), Some(0));
+        assert_eq!(minimum_swaps(b"ab", b"ba"), Some(1));
+        assert_eq!(minimum_swaps(b"bca", b"abc"), Some(2));
+        assert_eq!(minimum_swaps(b"badc", b"abcd"), Some(2));
+        // The duplicate letters make it possible to do it in two
+        // swaps instead of three
+        assert_eq!(minimum_swaps(b"baab", b"abba"), Some(2));
+        assert_eq!(minimum_swaps(b"aab", b"abb"), None);
+        assert_eq!(minimum_swaps(b"ab", b"ac"), None);
+    }
+
+    #[test]
+    fn minimum_swaps_matches_solve() {
+        // Try all of the rotations of some strings with repeated
+        // letters and compare with the full solver
+        for target in ["aabbcc", "abcabd", "aaabbc", "abcdef", "abacad"] {
+            let target = target.as_bytes();
+            let mut start = target.to_vec();
+
+            for _ in 0..target.len() {
+                start.rotate_left(1);
+
+                for i in 0..start.len() {
+                    start.swap(0, i);
+
+                    assert_eq!(
+                        minimum_swaps(&start, target),
+                        solve(&start, target).map(|s| s.len()),
+                        "{:?} {:?}",
+                        std::str::from_utf8(&start).unwrap(),
+                        std::str::from_utf8(target).unwrap(),
+                    );
+                }
+            }
+        }
+    }
+}
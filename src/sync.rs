@@ -0,0 +1,513 @@
+// Vaflo – A word game in Esperanto
+// Copyright (C) 2025  Neil Roberts
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+// Conversion of the save states to a short code that can be copied
+// to another device. The code is the output of
+// save_states_to_string compressed with a simple LZSS scheme,
+// followed by an Adler-32 checksum and encoded with the URL-safe
+// base64 alphabet so that it can also be put in a QR code.
+
+use std::fmt;
+use std::collections::HashMap;
+use super::save_state::{self, SaveState};
+
+const FORMAT_VERSION: u8 = 1;
+
+// Matches are encoded in two bytes with a 12-bit offset and a 4-bit
+// length
+const WINDOW_SIZE: usize = 1 << 12;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = MIN_MATCH + 15;
+// Maximum number of earlier positions to try for each match
+const MAX_CHAIN: usize = 64;
+
+const CHECKSUM_SIZE: usize = 4;
+
+static BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+#[derive(Debug)]
+pub enum DecodeError {
+    InvalidCharacter(char),
+    TooShort,
+    UnknownVersion(u8),
+    BadChecksum,
+    CorruptData,
+    InvalidUtf8,
+    InvalidSaveStates(save_state::LoadSaveStatesError),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::InvalidCharacter(ch) => {
+                write!(f, "invalid character “{}”", ch)
+            },
+            DecodeError::TooShort => write!(f, "the code is too short"),
+            DecodeError::UnknownVersion(version) => {
+                write!(f, "unknown format version {}", version)
+            },
+            DecodeError::BadChecksum => write!(f, "the checksum is wrong"),
+            DecodeError::CorruptData => {
+                write!(f, "the compressed data is corrupt")
+            },
+            DecodeError::InvalidUtf8 => {
+                write!(f, "the decompressed data is not valid UTF-8")
+            },
+            DecodeError::InvalidSaveStates(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MOD_ADLER: u32 = 65521;
+
+    let mut a = 1u32;
+    let mut b = 0u32;
+
+    for &byte in data {
+        a = (a + byte as u32) % MOD_ADLER;
+        b = (b + a) % MOD_ADLER;
+    }
+
+    (b << 16) | a
+}
+
+fn compress(data: &[u8]) -> Vec<u8> {
+    let mut result = Vec::new();
+    // Earlier positions of each three-byte sequence
+    let mut positions = HashMap::<[u8; MIN_MATCH], Vec<usize>>::new();
+    let mut pos = 0;
+
+    // Each group of eight tokens is preceded by a byte with a bit for
+    // each token which is set if the token is a match
+    let mut flags_pos = 0;
+    let mut n_tokens = 8;
+
+    while pos < data.len() {
+        if n_tokens >= 8 {
+            flags_pos = result.len();
+            result.push(0);
+            n_tokens = 0;
+        }
+
+        let mut best_length = 0;
+        let mut best_offset = 0;
+
+        if let Some(key) = data.get(pos..pos + MIN_MATCH) {
+            let key: [u8; MIN_MATCH] = key.try_into().unwrap();
+
+            if let Some(candidates) = positions.get(&key) {
+                for &start in candidates.iter().rev().take(MAX_CHAIN) {
+                    if pos - start > WINDOW_SIZE {
+                        break;
+                    }
+
+                    let length = data[start..]
+                        .iter()
+                        .zip(&data[pos..])
+                        .take(MAX_MATCH)
+                        .take_while(|(a, b)| a == b)
+                        .count();
+
+                    if length > best_length {
+                        best_length = length;
+                        best_offset = pos - start;
+                    }
+                }
+            }
+        }
+
+        let token_length = if best_length >= MIN_MATCH {
+            let value = ((best_offset - 1) << 4) | (best_length - MIN_MATCH);
+            result.push((value >> 8) as u8);
+            result.push(value as u8);
+            result[flags_pos] |= 1 << n_tokens;
+            best_length
+        } else {
+            result.push(data[pos]);
+            1
+        };
+
+        for p in pos..pos + token_length {
+            if let Some(key) = data.get(p..p + MIN_MATCH) {
+                positions.entry(key.try_into().unwrap())
+                    .or_default()
+                    .push(p);
+            }
+        }
+
+        pos += token_length;
+        n_tokens += 1;
+    }
+
+    result
+}
+
+fn decompress(data: &[u8]) -> Result<Vec<u8>, DecodeError> {
+    let mut result = Vec::new();
+    let mut bytes = data.iter();
+
+    while let Some(&flags) = bytes.next() {
+        for bit in 0..8 {
+            let Some(&byte) = bytes.next()
+            else {
+                break;
+            };
+
+            if flags & (1 << bit) == 0 {
+                result.push(byte);
+                continue;
+            }
+
+            let Some(&low_byte) = bytes.next()
+            else {
+                return Err(DecodeError::CorruptData);
+            };
+
+            let value = ((byte as usize) << 8) | low_byte as usize;
+            let offset = (value >> 4) + 1;
+            let length = (value & 0xf) + MIN_MATCH;
+
+            let Some(start) = result.len().checked_sub(offset)
+            else {
+                return Err(DecodeError::CorruptData);
+            };
+
+            // The match can overlap with the bytes being copied so
+            // they need to be copied one at a time
+            for i in start..start + length {
+                result.push(result[i]);
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+fn base64_encode(data: &[u8]) -> String {
+    let mut result = String::with_capacity(data.len().div_ceil(3) * 4);
+
+    for chunk in data.chunks(3) {
+        let value = chunk.iter()
+            .enumerate()
+            .fold(0u32, |value, (i, &byte)| {
+                value | ((byte as u32) << (16 - i * 8))
+            });
+
+        for i in 0..chunk.len() + 1 {
+            let index = (value >> (18 - i * 6)) & 0x3f;
+            result.push(BASE64_ALPHABET[index as usize] as char);
+        }
+    }
+
+    result
+}
+
+fn base64_decode(s: &str) -> Result<Vec<u8>, DecodeError> {
+    let mut result = Vec::with_capacity(s.len() * 3 / 4);
+    let mut value = 0u32;
+    let mut n_bits = 0;
+
+    // Ignore whitespace in case the code was broken over several
+    // lines when it was copied
+    for ch in s.chars().filter(|ch| !ch.is_whitespace()) {
+        let Some(index) = BASE64_ALPHABET.iter().position(|&b| b as char == ch)
+        else {
+            return Err(DecodeError::InvalidCharacter(ch));
+        };
+
+        value = (value << 6) | index as u32;
+        n_bits += 6;
+
+        if n_bits >= 8 {
+            n_bits -= 8;
+            result.push((value >> n_bits) as u8);
+            value &= (1 << n_bits) - 1;
+        }
+    }
+
+    Ok(result)
+}
+
+// Converts the string created by `save_states_to_string` into an
+// export code
+pub fn encode(save_states: &str) -> String {
+    let mut data = vec![FORMAT_VERSION];
+
+    data.extend_from_slice(&compress(save_states.as_bytes()));
+
+    let checksum = adler32(&data);
+    data.extend_from_slice(&checksum.to_be_bytes());
+
+    base64_encode(&data)
+}
+
+pub fn decode(code: &str) -> Result<HashMap<usize, SaveState>, DecodeError> {
+    let data = base64_decode(code)?;
+
+    if data.len() < 1 + CHECKSUM_SIZE {
+        return Err(DecodeError::TooShort);
+    }
+
+    let (data, checksum) = data.split_at(data.len() - CHECKSUM_SIZE);
+
+    if adler32(data) != u32::from_be_bytes(checksum.try_into().unwrap()) {
+        return Err(DecodeError::BadChecksum);
+    }
+
+    if data[0] != FORMAT_VERSION {
+        return Err(DecodeError::UnknownVersion(data[0]));
+    }
+
+    let Ok(save_states) = String::from_utf8(decompress(&data[1..])?)
+    else {
+        return Err(DecodeError::InvalidUtf8);
+    };
+
+    if save_states.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    save_state::load_save_states(&save_states)
+        .map_err(DecodeError::InvalidSaveStates)
+}
+
+// Higher numbers are considered a more complete state when merging
+fn completion_rank(save_state: &SaveState) -> u32 {
+    if save_state.grid().puzzle.is_solved() {
+        2
+    } else if save_state.swaps_remaining() == 0 {
+        1
+    } else {
+        0
+    }
+}
+
+fn should_replace(local: &SaveState, imported: &SaveState) -> bool {
+    let local_rank = completion_rank(local);
+    let imported_rank = completion_rank(imported);
+
+    if imported_rank != local_rank {
+        imported_rank > local_rank
+    } else if imported_rank == 0 {
+        // If both are still in progress then prefer the one that has
+        // had more swaps
+        imported.swaps_remaining() < local.swaps_remaining()
+    } else {
+        // Otherwise both games are finished so keep the local one
+        false
+    }
+}
+
+// Merges the imported save states into the local ones. A solved
+// puzzle takes priority over a failed one which takes priority over
+// one that is still in progress. Returns the number of puzzles that
+// were added or replaced.
+pub fn merge(
+    local: &mut HashMap<usize, SaveState>,
+    imported: HashMap<usize, SaveState>,
+) -> usize {
+    let mut n_changed = 0;
+
+    for (puzzle_num, imported_state) in imported {
+        let replace = local.get(&puzzle_num)
+            .map(|local_state| should_replace(local_state, &imported_state))
+            .unwrap_or(true);
+
+        if replace {
+            local.insert(puzzle_num, imported_state);
+            n_changed += 1;
+        }
+    }
+
+    n_changed
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn make_state(puzzle: &str, swaps_remaining: u32) -> SaveState {
+        format!("{}:{}", puzzle, swaps_remaining).parse().unwrap()
+    }
+
+    const SOLVED: &str = "MORSAUUKROLASDOOURSOJ\
+                          arcdnhfjvlmewpxbukoty";
+    const UNSOLVED: &str = "MORSAUUKROLASDOOURSOJ\
+                            ardxnhpfmvulwtybkeocj";
+
+    #[test]
+    fn compression() {
+        for data in [
+            "".as_bytes(),
+            b"a",
+            b"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+            b"abcabcabcabcXabcabcabcabcabcabcYabc",
+            "ĈĜĤĴŜŬĈĜĤĴŜŬĉĝĥĵŝŭ".as_bytes(),
+        ] {
+            let compressed = compress(data);
+            assert_eq!(&decompress(&compressed).unwrap(), data);
+        }
+
+        let data = (0..10_000u32)
+            .map(|i| (i * 7 % 251) as u8)
+            .collect::<Vec<u8>>();
+        assert_eq!(decompress(&compress(&data)).unwrap(), data);
+
+        let repeated = "1:".repeat(1000);
+        assert!(compress(repeated.as_bytes()).len() < 300);
+    }
+
+    #[test]
+    fn bad_compressed_data() {
+        // Match that points before the start of the data
+        assert!(matches!(
+            decompress(&[1, 0, 0]),
+            Err(DecodeError::CorruptData),
+        ));
+        // Truncated match
+        assert!(matches!(
+            decompress(&[1, 0]),
+            Err(DecodeError::CorruptData),
+        ));
+    }
+
+    #[test]
+    fn base64() {
+        for data in [
+            "".as_bytes(),
+            b"f",
+            b"fo",
+            b"foo",
+            b"foob",
+            b"fooba",
+            b"foobar",
+            &[0xff, 0xfe, 0x00, 0x80],
+        ] {
+            let encoded = base64_encode(data);
+            assert_eq!(&base64_decode(&encoded).unwrap(), data);
+        }
+
+        assert_eq!(&base64_encode(b"foobar"), "Zm9vYmFy");
+        assert_eq!(&base64_encode(&[0xfb, 0xff]), "-_8");
+        assert_eq!(&base64_decode("Zm9v\nYmFy").unwrap(), b"foobar");
+
+        assert!(matches!(
+            base64_decode("Zm9v+"),
+            Err(DecodeError::InvalidCharacter('+')),
+        ));
+    }
+
+    #[test]
+    fn test_adler32() {
+        assert_eq!(adler32(b""), 1);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e60398);
+    }
+
+    #[test]
+    fn round_trip() {
+        let states = format!(
+            "0:{}:3,1:{}:0,2:{}:12:h1:t95:d",
+            SOLVED,
+            UNSOLVED,
+            UNSOLVED,
+        );
+
+        let code = encode(&states);
+
+        assert!(code.chars().all(|ch| BASE64_ALPHABET.contains(&(ch as u8))));
+
+        let decoded = decode(&code).unwrap();
+
+        assert_eq!(decoded.len(), 3);
+        assert_eq!(decoded[&0].swaps_remaining(), 3);
+        assert!(decoded[&0].grid().puzzle.is_solved());
+        assert_eq!(decoded[&1].swaps_remaining(), 0);
+        assert_eq!(decoded[&2].hints_used(), 1);
+        assert_eq!(decoded[&2].time_taken(), Some(95));
+        assert!(decoded[&2].hard_mode());
+
+        assert!(decode(&encode("")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn bad_codes() {
+        let code = encode(&format!("0:{}:3", SOLVED));
+
+        assert!(matches!(decode("AAA"), Err(DecodeError::TooShort)));
+        assert!(matches!(
+            decode("AB*D"),
+            Err(DecodeError::InvalidCharacter('*')),
+        ));
+
+        // Change a character in the middle of the code
+        let mut corrupt = code.clone().into_bytes();
+        let mid = corrupt.len() / 2;
+        corrupt[mid] = if corrupt[mid] == b'A' { b'B' } else { b'A' };
+        let corrupt = String::from_utf8(corrupt).unwrap();
+
+        assert!(matches!(decode(&corrupt), Err(DecodeError::BadChecksum)));
+
+        let mut data = vec![FORMAT_VERSION + 1];
+        data.extend_from_slice(&adler32(&data).to_be_bytes());
+        assert!(matches!(
+            decode(&base64_encode(&data)),
+            Err(DecodeError::UnknownVersion(2)),
+        ));
+
+        assert!(matches!(
+            decode(&encode("not a save state")),
+            Err(DecodeError::InvalidSaveStates(_)),
+        ));
+    }
+
+    #[test]
+    fn merge_states() {
+        let mut local = HashMap::new();
+        local.insert(0, make_state(UNSOLVED, 10));
+        local.insert(1, make_state(SOLVED, 3));
+        local.insert(2, make_state(UNSOLVED, 0));
+        local.insert(3, make_state(UNSOLVED, 5));
+        local.insert(4, make_state(SOLVED, 2));
+
+        let mut imported = HashMap::new();
+        // Solved beats in progress
+        imported.insert(0, make_state(SOLVED, 4));
+        // Failed doesn’t beat solved
+        imported.insert(1, make_state(UNSOLVED, 0));
+        // Solved beats failed
+        imported.insert(2, make_state(SOLVED, 1));
+        // More swaps beats fewer swaps while in progress
+        imported.insert(3, make_state(UNSOLVED, 4));
+        // Finished games keep the local version
+        imported.insert(4, make_state(SOLVED, 4));
+        // New puzzles are added
+        imported.insert(5, make_state(UNSOLVED, 7));
+
+        assert_eq!(merge(&mut local, imported), 4);
+
+        assert_eq!(local.len(), 6);
+        assert!(local[&0].grid().puzzle.is_solved());
+        assert_eq!(local[&0].swaps_remaining(), 4);
+        assert!(local[&1].grid().puzzle.is_solved());
+        assert_eq!(local[&1].swaps_remaining(), 3);
+        assert!(local[&2].grid().puzzle.is_solved());
+        assert_eq!(local[&3].swaps_remaining(), 4);
+        assert_eq!(local[&4].swaps_remaining(), 2);
+        assert_eq!(local[&5].swaps_remaining(), 7);
+    }
+}
@@ -22,24 +22,77 @@ use std::fmt::Write;
 use super::stars::{MAXIMUM_SWAPS, MAXIMUM_STARS};
 use super::save_state;
 use save_state::SaveState;
+use super::settings::{Settings, Palette};
+use super::share_template::ShareTemplate;
+use super::hint;
+use super::sync;
+use super::date::Date;
+use super::daily_puzzle::{self, DailyPuzzle};
+use super::definitions::{self, Definitions};
+use super::dictionary::Dictionary;
+use super::practice;
+use super::puzzle_check;
+use rand::SeedableRng;
+use rand::rngs::SmallRng;
 use std::collections::HashMap;
 
 const STOP_ANIMATIONS_DELAY: i32 = 250;
 const REMOVE_NOTICE_DELAY: i32 = 3_250;
+const TIMER_INTERVAL: i32 = 1_000;
 const N_STARS: u32 = 5;
 const SAVE_STATE_KEY: &'static str = "vaflo-save-states";
+// Parts of the save states that couldn’t be parsed are moved here
+const SAVE_STATE_BACKUP_KEY: &'static str = "vaflo-save-states-backup";
+const SETTINGS_KEY: &'static str = "vaflo-settings";
+// Dictionary used to generate the practice puzzles
+const DICTIONARY_FILENAME: &'static str = "data/dictionary.bin";
+
+// Number of months to show in the statistics
+const N_STATS_MONTHS: usize = 12;
+
+static MONTH_NAMES: [&'static str; 12] = [
+    "januaro",
+    "februaro",
+    "marto",
+    "aprilo",
+    "majo",
+    "junio",
+    "julio",
+    "aŭgusto",
+    "septembro",
+    "oktobro",
+    "novembro",
+    "decembro",
+];
+
+static WEEKDAY_NAMES: [&'static str; 7] = [
+    "lundo",
+    "mardo",
+    "merkredo",
+    "ĵaŭdo",
+    "vendredo",
+    "sabato",
+    "dimanĉo",
+];
+
+// Message shown when today’s puzzle isn’t on the server
+const NO_PUZZLE_MESSAGE: &'static str =
+    "Ankoraŭ ne estas puzlo por hodiaŭ. Bonvolu reveni poste.";
+// Message shown when today’s puzzle can’t be downloaded and the
+// service worker doesn’t have it in its cache either
+const OFFLINE_MESSAGE: &'static str =
+    "Ne eblis elŝuti la hodiaŭan puzlon. Bonvolu kontroli vian retkonekton.";
+// Message shown when today’s puzzle fails the checks in puzzle_check
+const BROKEN_PUZZLE_MESSAGE: &'static str =
+    "Bedaŭrinde la hodiaŭa puzlo estas difektita. Bonvolu reveni poste.";
 
-const FIRST_PUZZLE_DATE: &'static str = "2023-08-07T00:00:00Z";
-
-// For some reason js_sys only has the form with year and month for
-// Date.UTC
-#[wasm_bindgen]
-extern "C" {
-    #[wasm_bindgen(js_namespace = Date, js_name = UTC)]
-    pub fn date_utc_ymd(year: u32, month_index: u32, day: u32) -> f64;
+fn show_error(message: &str) {
+    show_error_with_message(message, "Eraro okazis");
 }
 
-fn show_error(message: &str) {
+// Logs the error message to the console and shows a different
+// message to the user
+fn show_error_with_message(message: &str, user_message: &str) {
     console::log_1(&message.into());
 
     let Some(window) = web_sys::window()
@@ -57,29 +110,18 @@ fn show_error(message: &str) {
         return;
     };
 
-    message_elem.set_text_content(Some("Eraro okazis"));
+    message_elem.set_text_content(Some(user_message));
 }
 
-fn todays_puzzle_number(puzzles: &[Grid]) -> Option<usize> {
-    let first_date = js_sys::Date::parse(FIRST_PUZZLE_DATE);
+// Today’s date in the local time zone
+fn today() -> Date {
     let today = js_sys::Date::new_0();
-    let today_utc = date_utc_ymd(
-        today.get_full_year(),
-        today.get_month(),
+
+    Date::new(
+        today.get_full_year() as i32,
+        today.get_month() + 1,
         today.get_date(),
-    );
-    // Both dates are taken at midnight of the start of the day in UTC
-    // time so the difference should be a whole number of days, unless
-    // there are leap seconds or something. Using round() should
-    // compensate for the leap seconds and any floating-point
-    // inaccurarcies.
-    let days = ((today_utc - first_date) / (24.0 * 3_600_000.0)).round();
-
-    if days.is_finite() && days >= 0.0 && days < puzzles.len() as f64 {
-        Some(days as usize)
-    } else {
-        None
-    }
+    )
 }
 
 struct Context {
@@ -162,7 +204,8 @@ impl Loader {
     }
 
     fn queue_data_load(&mut self) {
-        let filename = "puzzles.txt";
+        let today = today();
+        let filename = daily_puzzle::path(today);
 
         let floating_pointer = self.floating_pointer.unwrap();
 
@@ -175,6 +218,26 @@ impl Loader {
             };
 
             let response: web_sys::Response = v.dyn_into().unwrap();
+
+            if !response.ok() {
+                let status = response.status();
+
+                show_error_with_message(
+                    &format!("Error fetching today’s puzzle: {}", status),
+                    // The service worker responds with 503 if we’re
+                    // offline and the puzzle isn’t in the cache
+                    if status == 404 {
+                        NO_PUZZLE_MESSAGE
+                    } else {
+                        OFFLINE_MESSAGE
+                    },
+                );
+                unsafe {
+                    (*floating_pointer).stop_floating();
+                }
+                return;
+            }
+
             let promise = match response.array_buffer() {
                 Ok(p) => p,
                 Err(_) => {
@@ -192,12 +255,12 @@ impl Loader {
             let data = js_sys::Uint8Array::new(&v).to_vec();
 
             unsafe {
-                (*floating_pointer).data_loaded(data);
+                (*floating_pointer).data_loaded(today, data);
             }
         });
 
         let error_closure = PromiseClosure::new(move |_| {
-            show_error("Error loading data");
+            show_error_with_message("Error loading data", OFFLINE_MESSAGE);
             unsafe {
                 (*floating_pointer).stop_floating();
             }
@@ -207,7 +270,7 @@ impl Loader {
         request_init.cache(web_sys::RequestCache::NoCache);
 
         let promise = self.context.window.fetch_with_str_and_init(
-            filename,
+            &filename,
             &request_init,
         );
 
@@ -218,50 +281,52 @@ impl Loader {
         self.data_error_closure = Some(error_closure);
     }
 
-    fn parse_puzzles(&mut self, data: Vec<u8>) -> Result<Vec<Grid>, ()> {
-        let Ok(data) = std::str::from_utf8(&data)
-        else {
-            show_error("Puzzle data contains invalid UTF-8");
-            return Err(());
-        };
-
-        let mut puzzles = Vec::new();
-
-        for (line_num, line) in data.lines().enumerate() {
-            match line.parse::<Grid>() {
-                Ok(puzzle) => puzzles.push(puzzle),
-                Err(e) => {
-                    show_error(&format!(
-                        "puzzles.txt: line {}: {}",
-                        line_num + 1,
-                        e,
-                    ));
-                    return Err(());
-                },
-            }
-        }
-
-        if puzzles.is_empty() {
-            show_error("puzzles.txt is empty");
-            return Err(());
-        }
+    fn data_loaded(&mut self, date: Date, data: Vec<u8>) {
+        match daily_puzzle::decode(date, &data) {
+            Err(e) => {
+                show_error(&format!("{}: {}", daily_puzzle::path(date), e));
+                self.stop_floating();
+            },
+            Ok(puzzle) => {
+                let problems = puzzle_check::check(&puzzle.grid);
+
+                if !problems.is_empty() {
+                    for problem in problems.iter() {
+                        console::log_1(&format!(
+                            "{}: {}",
+                            daily_puzzle::path(date),
+                            problem,
+                        ).into());
+                    }
 
-        Ok(puzzles)
-    }
+                    show_error_with_message(
+                        "today’s puzzle is broken",
+                        BROKEN_PUZZLE_MESSAGE,
+                    );
+                    self.stop_floating();
+                    return;
+                }
 
-    fn data_loaded(&mut self, data: Vec<u8>) {
-        match self.parse_puzzles(data) {
-            Err(_) => {
-                self.stop_floating();
+                match date.puzzle_number() {
+                    Some(puzzle_num) => self.start_game(puzzle_num, puzzle),
+                    None => {
+                        show_error("today is before the first puzzle");
+                        self.stop_floating();
+                    },
+                }
             },
-            Ok(puzzles) => self.start_game(puzzles),
         }
     }
 
-    fn start_game(&mut self, puzzles: Vec<Grid>) {
+    fn start_game(&mut self, todays_puzzle: usize, puzzle: DailyPuzzle) {
         let Loader { context, .. } = self.stop_floating();
 
-        match Vaflo::new(context, puzzles) {
+        match Vaflo::new(
+            context,
+            todays_puzzle,
+            puzzle.grid,
+            puzzle.definitions,
+        ) {
             Ok(vaflo) => {
                 // Leak the main vaflo object so that it will live as
                 // long as the web page
@@ -295,6 +360,17 @@ struct Vaflo {
     share_closure: Option<Closure::<dyn Fn(JsValue)>>,
     close_closure: Option<Closure::<dyn Fn(JsValue)>>,
     help_closure: Option<Closure::<dyn Fn(JsValue)>>,
+    settings_closure: Option<Closure::<dyn Fn(JsValue)>>,
+    hint_closure: Option<Closure::<dyn Fn(JsValue)>>,
+    export_closure: Option<Closure::<dyn Fn(JsValue)>>,
+    import_closure: Option<Closure::<dyn Fn(JsValue)>>,
+    practice_closure: Option<Closure::<dyn Fn(JsValue)>>,
+    generate_closure: Option<Closure::<dyn Fn()>>,
+    dictionary_response_closure: Option<PromiseClosure>,
+    dictionary_content_closure: Option<PromiseClosure>,
+    dictionary_error_closure: Option<PromiseClosure>,
+    timer_closure: Option<Closure::<dyn Fn()>>,
+    timer_interval_handle: Option<i32>,
     game_contents: web_sys::HtmlElement,
     game_grid: web_sys::HtmlElement,
     letters: Vec<web_sys::HtmlElement>,
@@ -307,15 +383,54 @@ struct Vaflo {
     stop_animations_queued: bool,
     animated_letters: Vec<usize>,
     swaps_remaining: u32,
+    hints_used: u32,
+    // The two positions of the last hint that haven’t been swapped yet
+    hint: Option<(usize, usize)>,
+    // Milliseconds spent on the puzzle, not including the time since
+    // timer_resumed_at. This is None if the first swap hasn’t been
+    // made yet.
+    time_taken: Option<f64>,
+    // The time when the timer was last started if it is running
+    timer_resumed_at: Option<f64>,
+    // Whether today’s puzzle is being played in hard mode. This can
+    // only be changed before the first swap.
+    hard_mode: bool,
+    // The puzzle number of the day when the player first saved
+    // today’s puzzle. This can be later than todays_puzzle if the
+    // page was left open past midnight.
+    played_on: Option<usize>,
     save_state_dirty: bool,
+    // Set while the page is reloading after importing save states so
+    // that the stale state for today doesn’t overwrite the imported
+    // one when the page is unloaded
+    reloading: bool,
     statistics: Option<save_state::Statistics>,
     notice_element: Option<web_sys::HtmlElement>,
     notice_closure: Option<Closure::<dyn Fn()>>,
     notice_timeout_handle: Option<i32>,
+    settings: Settings,
+    // The site’s share template if it has one
+    share_template: Option<ShareTemplate>,
+    // Definitions of the words in the puzzle to show at the end
+    definitions: Definitions,
+    // Whether a generated practice puzzle is being played instead of
+    // today’s puzzle. Practice puzzles aren’t saved and don’t count
+    // in the statistics.
+    is_practice: bool,
+    // The dictionary is only downloaded the first time a practice
+    // puzzle is wanted
+    dictionary: Option<Dictionary>,
+    loading_dictionary: bool,
+    rng: SmallRng,
 }
 
 impl Vaflo {
-    fn new(context: Context, puzzles: Vec<Grid>) -> Result<Box<Vaflo>, String> {
+    fn new(
+        context: Context,
+        todays_puzzle: usize,
+        todays_grid: Grid,
+        definitions: Definitions,
+    ) -> Result<Box<Vaflo>, String> {
         let Some(game_contents) =
             context.document.get_element_by_id("game-contents")
             .and_then(|c| c.dyn_into::<web_sys::HtmlElement>().ok())
@@ -336,20 +451,24 @@ impl Vaflo {
             return Err("failed to get swaps remaining message".to_string());
         };
 
-        let Some(todays_puzzle) = todays_puzzle_number(&puzzles)
-        else {
-            return Err("there is no puzzle for today".to_string());
-        };
-
-        let mut save_states = load_save_states(&context);
+        let (mut save_states, had_dropped_save_states) =
+            load_save_states(&context);
+        let settings = load_settings(&context);
+        let share_template = load_share_template(&context);
 
         let is_first_game = save_states.is_empty();
 
         let save_state = save_states.remove(&todays_puzzle)
             .unwrap_or_else(|| {
-                SaveState::new(puzzles[todays_puzzle].clone(), MAXIMUM_SWAPS)
+                SaveState::new(todays_grid, MAXIMUM_SWAPS)
             });
 
+        let hard_mode = if save_state.swaps_remaining() < MAXIMUM_SWAPS {
+            save_state.hard_mode()
+        } else {
+            settings.hard_mode
+        };
+
         let mut vaflo = Box::new(Vaflo {
             context,
             pointerdown_closure: None,
@@ -360,6 +479,17 @@ impl Vaflo {
             share_closure: None,
             close_closure: None,
             help_closure: None,
+            settings_closure: None,
+            hint_closure: None,
+            export_closure: None,
+            import_closure: None,
+            practice_closure: None,
+            generate_closure: None,
+            dictionary_response_closure: None,
+            dictionary_content_closure: None,
+            dictionary_error_closure: None,
+            timer_closure: None,
+            timer_interval_handle: None,
             game_contents,
             game_grid,
             swaps_remaining_message,
@@ -372,20 +502,40 @@ impl Vaflo {
             stop_animations_queued: false,
             animated_letters: Vec::new(),
             swaps_remaining: save_state.swaps_remaining(),
+            hints_used: save_state.hints_used(),
+            hint: None,
+            time_taken: save_state.time_taken()
+                .map(|time_taken| time_taken as f64 * 1000.0),
+            timer_resumed_at: None,
+            hard_mode,
+            played_on: save_state.played_on(),
             save_state_dirty: false,
+            reloading: false,
             statistics: None,
             notice_closure: None,
             notice_element: None,
             notice_timeout_handle: None,
+            settings,
+            share_template,
+            definitions,
+            is_practice: false,
+            dictionary: None,
+            loading_dictionary: false,
+            rng: SmallRng::seed_from_u64(random_seed()),
         });
 
         vaflo.create_closures();
         vaflo.set_up_share_button();
         vaflo.set_up_close_button();
         vaflo.set_up_help_button();
+        vaflo.set_up_settings();
+        vaflo.set_up_hint_button();
+        vaflo.set_up_sync_buttons();
+        vaflo.set_up_practice_button();
         vaflo.create_letters()?;
         vaflo.update_title();
         vaflo.update_square_letters();
+        vaflo.update_grid_square_states();
         vaflo.update_square_states();
 
         if vaflo.check_end_state() {
@@ -393,14 +543,28 @@ impl Vaflo {
         } else {
             vaflo.update_game_state();
             vaflo.update_swaps_remaining();
+            vaflo.show_element_as_block("hint-button");
+
+            if vaflo.time_taken.is_some() {
+                vaflo.resume_timer();
+            }
         }
 
+        vaflo.update_timer_display();
+        vaflo.update_timer_interval();
+
         vaflo.show_game_contents();
 
         if is_first_game {
             vaflo.set_instructions_visibility(true);
         }
 
+        if had_dropped_save_states {
+            vaflo.show_notice(
+                "Kelkaj konservitaj ludoj estis difektitaj kaj estis forigitaj"
+            );
+        }
+
         Ok(vaflo)
     }
 
@@ -470,7 +634,7 @@ impl Vaflo {
         let visibility_closure = Closure::<dyn Fn(JsValue)>::new(
             move |_event: JsValue| {
                 let vaflo = unsafe { &mut *vaflo_pointer };
-                vaflo.save_to_local_storage();
+                vaflo.handle_visibility_change();
             }
         );
 
@@ -482,6 +646,103 @@ impl Vaflo {
         self.visibility_closure = Some(visibility_closure);
     }
 
+    fn handle_visibility_change(&mut self) {
+        // Don’t count the time while the page is hidden
+        if self.context.document.hidden() {
+            self.pause_timer();
+        } else if self.time_taken.is_some()
+            && self.game_state == GameState::Playing
+        {
+            self.resume_timer();
+        }
+
+        self.save_to_local_storage();
+    }
+
+    fn resume_timer(&mut self) {
+        if self.timer_resumed_at.is_none() {
+            self.time_taken.get_or_insert(0.0);
+            self.timer_resumed_at = Some(js_sys::Date::now());
+        }
+    }
+
+    fn pause_timer(&mut self) {
+        if let Some(resumed_at) = self.timer_resumed_at.take() {
+            let time_taken = self.time_taken.get_or_insert(0.0);
+            *time_taken += js_sys::Date::now() - resumed_at;
+            self.save_state_dirty = true;
+        }
+    }
+
+    fn elapsed_seconds(&self) -> Option<u32> {
+        self.time_taken.map(|time_taken| {
+            let running_time = self.timer_resumed_at
+                .map(|resumed_at| js_sys::Date::now() - resumed_at)
+                .unwrap_or(0.0);
+
+            ((time_taken + running_time) / 1000.0).round() as u32
+        })
+    }
+
+    fn update_timer_display(&self) {
+        if !self.settings.timer {
+            self.hide_element("timer");
+            return;
+        }
+
+        if let Some(element) = self.context.document.get_element_by_id("timer")
+            .and_then(|c| c.dyn_into::<web_sys::HtmlElement>().ok())
+        {
+            let text = format!(
+                "⏱️ {}",
+                save_state::format_time(self.elapsed_seconds().unwrap_or(0)),
+            );
+            self.set_element_text(&element, &text);
+            let _ = element.style().set_property("display", "block");
+        }
+    }
+
+    fn update_timer_interval(&mut self) {
+        let want_interval = self.settings.timer
+            && self.game_state == GameState::Playing;
+
+        if !want_interval {
+            if let Some(handle) = self.timer_interval_handle.take() {
+                self.context.window.clear_interval_with_handle(handle);
+            }
+            return;
+        }
+
+        if self.timer_interval_handle.is_some() {
+            return;
+        }
+
+        let vaflo_pointer = self as *mut Vaflo;
+
+        let closure = self.timer_closure.get_or_insert_with(|| {
+            Closure::<dyn Fn()>::new(move || {
+                let vaflo = unsafe { &mut *vaflo_pointer };
+                vaflo.update_timer_display();
+            })
+        });
+
+        match self
+            .context
+            .window
+            .set_interval_with_callback_and_timeout_and_arguments_0(
+                closure.as_ref().unchecked_ref(),
+                TIMER_INTERVAL,
+            )
+        {
+            Ok(handle) => {
+                self.timer_interval_handle = Some(handle);
+            },
+            Err(_) => {
+                console::log_1(&"Error setting interval".into());
+            },
+        }
+    }
+
     fn set_up_share_button(&mut self) {
         let vaflo_pointer = self as *mut Vaflo;
 
@@ -497,66 +758,551 @@ impl Vaflo {
             .and_then(|c| c.dyn_into::<web_sys::HtmlElement>().ok())
         else {
             return;
-        };
-
-        let _ = share_button.add_event_listener_with_callback(
-            "click",
-            share_closure.as_ref().unchecked_ref(),
-        );
+        };
+
+        let _ = share_button.add_event_listener_with_callback(
+            "click",
+            share_closure.as_ref().unchecked_ref(),
+        );
+
+        self.share_closure = Some(share_closure);
+    }
+
+    fn set_up_close_button(&mut self) {
+        let vaflo_pointer = self as *mut Vaflo;
+
+        let close_closure = Closure::<dyn Fn(JsValue)>::new(
+            move |_event: JsValue| {
+                let vaflo = unsafe { &mut *vaflo_pointer };
+                vaflo.set_instructions_visibility(false);
+            }
+        );
+
+        for id in ["close-instructions", "close-instructions-cross"] {
+            let Some(close_button) =
+                self.context.document.get_element_by_id(id)
+                .and_then(|c| c.dyn_into::<web_sys::EventTarget>().ok())
+            else {
+                continue;
+            };
+
+            let _ = close_button.add_event_listener_with_callback(
+                "click",
+                close_closure.as_ref().unchecked_ref(),
+            );
+        }
+
+        self.close_closure = Some(close_closure);
+    }
+
+    fn set_up_help_button(&mut self) {
+        let vaflo_pointer = self as *mut Vaflo;
+
+        let help_closure = Closure::<dyn Fn(JsValue)>::new(
+            move |_event: JsValue| {
+                let vaflo = unsafe { &mut *vaflo_pointer };
+                vaflo.set_instructions_visibility(true);
+            }
+        );
+
+        let Some(help_button) =
+            self.context.document.get_element_by_id("help-button")
+            .and_then(|c| c.dyn_into::<web_sys::EventTarget>().ok())
+        else {
+            return;
+        };
+
+        let _ = help_button.add_event_listener_with_callback(
+            "click",
+            help_closure.as_ref().unchecked_ref(),
+        );
+
+        self.help_closure = Some(help_closure);
+    }
+
+    fn set_up_settings(&mut self) {
+        let vaflo_pointer = self as *mut Vaflo;
+
+        let settings_closure = Closure::<dyn Fn(JsValue)>::new(
+            move |_event: JsValue| {
+                let vaflo = unsafe { &mut *vaflo_pointer };
+                vaflo.handle_settings_change();
+            }
+        );
+
+        if let Some(palette_select) = self.palette_select() {
+            palette_select.set_value(self.settings.palette.name());
+
+            let _ = palette_select.add_event_listener_with_callback(
+                "change",
+                settings_closure.as_ref().unchecked_ref(),
+            );
+        }
+
+        if let Some(markers_checkbox) = self.markers_checkbox() {
+            markers_checkbox.set_checked(self.settings.markers);
+
+            let _ = markers_checkbox.add_event_listener_with_callback(
+                "change",
+                settings_closure.as_ref().unchecked_ref(),
+            );
+        }
+
+        if let Some(timer_checkbox) = self.timer_checkbox() {
+            timer_checkbox.set_checked(self.settings.timer);
+
+            let _ = timer_checkbox.add_event_listener_with_callback(
+                "change",
+                settings_closure.as_ref().unchecked_ref(),
+            );
+        }
+
+        if let Some(hard_mode_checkbox) = self.hard_mode_checkbox() {
+            hard_mode_checkbox.set_checked(self.settings.hard_mode);
+
+            let _ = hard_mode_checkbox.add_event_listener_with_callback(
+                "change",
+                settings_closure.as_ref().unchecked_ref(),
+            );
+        }
+
+        if let Some(grace_days_select) = self.grace_days_select() {
+            grace_days_select.set_value(
+                &self.settings.streak_rules.grace_days.to_string()
+            );
+
+            let _ = grace_days_select.add_event_listener_with_callback(
+                "change",
+                settings_closure.as_ref().unchecked_ref(),
+            );
+        }
+
+        if let Some(same_day_checkbox) = self.same_day_checkbox() {
+            same_day_checkbox.set_checked(
+                self.settings.streak_rules.same_day_only
+            );
+
+            let _ = same_day_checkbox.add_event_listener_with_callback(
+                "change",
+                settings_closure.as_ref().unchecked_ref(),
+            );
+        }
+
+        self.settings_closure = Some(settings_closure);
+    }
+
+    fn set_up_hint_button(&mut self) {
+        let vaflo_pointer = self as *mut Vaflo;
+
+        let hint_closure = Closure::<dyn Fn(JsValue)>::new(
+            move |_event: JsValue| {
+                let vaflo = unsafe { &mut *vaflo_pointer };
+                vaflo.show_hint();
+            }
+        );
+
+        let Some(hint_button) =
+            self.context.document.get_element_by_id("hint-button")
+            .and_then(|c| c.dyn_into::<web_sys::EventTarget>().ok())
+        else {
+            return;
+        };
+
+        let _ = hint_button.add_event_listener_with_callback(
+            "click",
+            hint_closure.as_ref().unchecked_ref(),
+        );
+
+        self.hint_closure = Some(hint_closure);
+    }
+
+    fn set_up_sync_buttons(&mut self) {
+        let vaflo_pointer = self as *mut Vaflo;
+
+        let export_closure = Closure::<dyn Fn(JsValue)>::new(
+            move |_event: JsValue| {
+                let vaflo = unsafe { &mut *vaflo_pointer };
+                vaflo.export_save_states();
+            }
+        );
+
+        if let Some(export_button) =
+            self.context.document.get_element_by_id("export-button")
+            .and_then(|c| c.dyn_into::<web_sys::EventTarget>().ok())
+        {
+            let _ = export_button.add_event_listener_with_callback(
+                "click",
+                export_closure.as_ref().unchecked_ref(),
+            );
+        }
+
+        self.export_closure = Some(export_closure);
+
+        let import_closure = Closure::<dyn Fn(JsValue)>::new(
+            move |_event: JsValue| {
+                let vaflo = unsafe { &mut *vaflo_pointer };
+                vaflo.import_save_states();
+            }
+        );
+
+        if let Some(import_button) =
+            self.context.document.get_element_by_id("import-button")
+            .and_then(|c| c.dyn_into::<web_sys::EventTarget>().ok())
+        {
+            let _ = import_button.add_event_listener_with_callback(
+                "click",
+                import_closure.as_ref().unchecked_ref(),
+            );
+        }
+
+        self.import_closure = Some(import_closure);
+    }
+
+    fn export_save_states(&mut self) {
+        // Make sure the current state of today’s puzzle is included
+        self.save_state_dirty = true;
+        self.save_to_local_storage();
+
+        let (save_states, _) = load_save_states(&self.context);
+        let code = sync::encode(
+            &save_state::save_states_to_string(save_states)
+        );
+
+        match self.set_clipboard_text(&code) {
+            Ok(()) => self.show_notice("Kodo kopiita al la tondujo"),
+            Err(e) => console::log_1(&e.into()),
+        }
+    }
+
+    fn import_save_states(&mut self) {
+        let code = match self.context.window.prompt_with_message(
+            "Algluu la kodon el la alia aparato",
+        ) {
+            Ok(Some(code)) => code,
+            Ok(None) => return,
+            Err(_) => {
+                console::log_1(&"Error showing prompt".into());
+                return;
+            },
+        };
+
+        let imported = match sync::decode(&code) {
+            Ok(imported) => imported,
+            Err(e) => {
+                console::log_1(&format!("Error importing code: {}", e).into());
+                self.show_notice("La kodo ne validas");
+                return;
+            },
+        };
+
+        self.save_state_dirty = true;
+        self.save_to_local_storage();
+
+        let Some(local_storage) = get_local_storage(&self.context)
+        else {
+            return;
+        };
+
+        let (mut save_states, _) =
+            load_save_states_from_local_storage(&local_storage);
+
+        if sync::merge(&mut save_states, imported) == 0 {
+            self.show_notice("Neniu nova ludo troviĝis en la kodo");
+            return;
+        }
+
+        if let Err(_) = local_storage.set_item(
+            SAVE_STATE_KEY,
+            &save_state::save_states_to_string(save_states),
+        ) {
+            console::log_1(&"Error saving state".into());
+            return;
+        }
+
+        // Reload the page so that today’s puzzle and the statistics
+        // are updated from the merged save states
+        self.reloading = true;
+
+        if let Err(_) = self.context.window.location().reload() {
+            console::log_1(&"Error reloading the page".into());
+            self.reloading = false;
+        }
+    }
+
+    fn set_up_practice_button(&mut self) {
+        let vaflo_pointer = self as *mut Vaflo;
+
+        let practice_closure = Closure::<dyn Fn(JsValue)>::new(
+            move |_event: JsValue| {
+                let vaflo = unsafe { &mut *vaflo_pointer };
+                vaflo.start_practice();
+            }
+        );
+
+        let Some(practice_button) =
+            self.context.document.get_element_by_id("practice-button")
+            .and_then(|c| c.dyn_into::<web_sys::EventTarget>().ok())
+        else {
+            return;
+        };
+
+        let _ = practice_button.add_event_listener_with_callback(
+            "click",
+            practice_closure.as_ref().unchecked_ref(),
+        );
+
+        self.practice_closure = Some(practice_closure);
+    }
+
+    fn start_practice(&mut self) {
+        if self.game_state == GameState::Playing
+            || self.loading_dictionary
+            || !self.animated_letters.is_empty()
+        {
+            return;
+        }
+
+        if self.dictionary.is_none() {
+            self.queue_dictionary_load();
+            return;
+        }
+
+        self.hide_element("practice-button");
+        self.set_element_text(
+            &self.swaps_remaining_message,
+            "Kreante puzlon…",
+        );
+
+        // Generating the puzzle can take a moment so let the browser
+        // show the message first
+        let vaflo_pointer = self as *mut Vaflo;
+
+        let closure = self.generate_closure.get_or_insert_with(|| {
+            Closure::<dyn Fn()>::new(move || {
+                let vaflo = unsafe { &mut *vaflo_pointer };
+                vaflo.generate_practice_puzzle();
+            })
+        });
+
+        if let Err(_) = self
+            .context
+            .window
+            .set_timeout_with_callback_and_timeout_and_arguments_0(
+                closure.as_ref().unchecked_ref(),
+                0,
+            )
+        {
+            console::log_1(&"Error setting timeout".into());
+            self.show_element_as_block("practice-button");
+        }
+    }
+
+    fn queue_dictionary_load(&mut self) {
+        let vaflo_pointer = self as *mut Vaflo;
+
+        let response_closure = PromiseClosure::new(move |v: JsValue| {
+            let vaflo = unsafe { &mut *vaflo_pointer };
+
+            let response: web_sys::Response = v.dyn_into().unwrap();
+
+            if !response.ok() {
+                vaflo.dictionary_load_failed();
+                return;
+            }
+
+            match response.array_buffer() {
+                Ok(promise) => {
+                    let _ = promise.then2(
+                        vaflo.dictionary_content_closure.as_ref().unwrap(),
+                        vaflo.dictionary_error_closure.as_ref().unwrap(),
+                    );
+                },
+                Err(_) => vaflo.dictionary_load_failed(),
+            }
+        });
+
+        let content_closure = PromiseClosure::new(move |v| {
+            let vaflo = unsafe { &mut *vaflo_pointer };
+            let data = js_sys::Uint8Array::new(&v).to_vec();
+
+            vaflo.dictionary = Some(Dictionary::new(data.into_boxed_slice()));
+            vaflo.loading_dictionary = false;
+            vaflo.start_practice();
+        });
+
+        let error_closure = PromiseClosure::new(move |_| {
+            let vaflo = unsafe { &mut *vaflo_pointer };
+            vaflo.dictionary_load_failed();
+        });
+
+        let promise = self.context.window.fetch_with_str(DICTIONARY_FILENAME);
+
+        let _ = promise.then2(&response_closure, &error_closure);
+
+        self.dictionary_response_closure = Some(response_closure);
+        self.dictionary_content_closure = Some(content_closure);
+        self.dictionary_error_closure = Some(error_closure);
+        self.loading_dictionary = true;
+    }
+
+    fn dictionary_load_failed(&mut self) {
+        console::log_1(&"Error loading the dictionary".into());
+        self.loading_dictionary = false;
+        self.show_notice("Ne eblis elŝuti la vortaron");
+    }
+
+    fn generate_practice_puzzle(&mut self) {
+        let Some(dictionary) = self.dictionary.as_ref()
+        else {
+            return;
+        };
+
+        match practice::generate(dictionary, &mut self.rng) {
+            Some(grid) => self.play_practice_puzzle(grid),
+            None => {
+                console::log_1(&"Failed to generate a puzzle".into());
+                self.show_notice("Ne eblis krei puzlon");
+                self.show_end_text();
+            },
+        }
+    }
+
+    fn play_practice_puzzle(&mut self, grid: Grid) {
+        // Make sure today’s puzzle is saved before replacing it
+        self.save_to_local_storage();
+
+        self.is_practice = true;
+        self.grid = grid;
+        self.definitions = Definitions::new();
+        self.swaps_remaining = MAXIMUM_SWAPS;
+        self.hints_used = 0;
+        self.hint = None;
+        self.time_taken = None;
+        self.timer_resumed_at = None;
+        self.hard_mode = self.settings.hard_mode;
+        self.save_state_dirty = false;
+        self.set_game_state(GameState::Playing);
+
+        self.update_title();
+        self.update_grid_square_states();
+        self.update_square_letters();
+        self.update_square_states();
+        self.update_swaps_remaining();
+
+        self.hide_element("statistics");
+        self.hide_element("definitions");
+        self.hide_element("share-button");
+        self.hide_element("practice-button");
+        self.show_element_as_block("hint-button");
+
+        self.update_timer_display();
+        self.update_timer_interval();
+    }
+
+    fn palette_select(&self) -> Option<web_sys::HtmlSelectElement> {
+        self.context.document.get_element_by_id("palette-select")
+            .and_then(|c| c.dyn_into::<web_sys::HtmlSelectElement>().ok())
+    }
+
+    fn markers_checkbox(&self) -> Option<web_sys::HtmlInputElement> {
+        self.context.document.get_element_by_id("markers-checkbox")
+            .and_then(|c| c.dyn_into::<web_sys::HtmlInputElement>().ok())
+    }
+
+    fn timer_checkbox(&self) -> Option<web_sys::HtmlInputElement> {
+        self.context.document.get_element_by_id("timer-checkbox")
+            .and_then(|c| c.dyn_into::<web_sys::HtmlInputElement>().ok())
+    }
+
+    fn grace_days_select(&self) -> Option<web_sys::HtmlSelectElement> {
+        self.context.document.get_element_by_id("grace-days-select")
+            .and_then(|c| c.dyn_into::<web_sys::HtmlSelectElement>().ok())
+    }
+
+    fn same_day_checkbox(&self) -> Option<web_sys::HtmlInputElement> {
+        self.context.document.get_element_by_id("same-day-checkbox")
+            .and_then(|c| c.dyn_into::<web_sys::HtmlInputElement>().ok())
+    }
+
+    fn hard_mode_checkbox(&self) -> Option<web_sys::HtmlInputElement> {
+        self.context.document.get_element_by_id("hard-mode-checkbox")
+            .and_then(|c| c.dyn_into::<web_sys::HtmlInputElement>().ok())
+    }
+
+    fn update_grid_square_states(&mut self) {
+        if self.hard_mode {
+            self.grid.update_square_states_hard();
+        } else {
+            self.grid.update_square_states();
+        }
+    }
+
+    fn update_hard_mode(&mut self) {
+        if self.settings.hard_mode == self.hard_mode {
+            return;
+        }
+
+        // The mode can’t be changed once today’s puzzle has been
+        // started, so it will only take effect from the next puzzle
+        if self.swaps_remaining < MAXIMUM_SWAPS
+            || self.game_state != GameState::Playing
+        {
+            self.show_notice(
+                "La malfacila reĝimo ŝanĝiĝos por la venonta puzlo"
+            );
+            return;
+        }
 
-        self.share_closure = Some(share_closure);
+        self.hard_mode = self.settings.hard_mode;
+        self.update_grid_square_states();
+        self.update_title();
     }
 
-    fn set_up_close_button(&mut self) {
-        let vaflo_pointer = self as *mut Vaflo;
-
-        let close_closure = Closure::<dyn Fn(JsValue)>::new(
-            move |_event: JsValue| {
-                let vaflo = unsafe { &mut *vaflo_pointer };
-                vaflo.set_instructions_visibility(false);
+    fn handle_settings_change(&mut self) {
+        if let Some(palette_select) = self.palette_select() {
+            match palette_select.value().parse::<Palette>() {
+                Ok(palette) => self.settings.palette = palette,
+                Err(e) => console::log_1(&e.to_string().into()),
             }
-        );
-
-        for id in ["close-instructions", "close-instructions-cross"] {
-            let Some(close_button) =
-                self.context.document.get_element_by_id(id)
-                .and_then(|c| c.dyn_into::<web_sys::EventTarget>().ok())
-            else {
-                continue;
-            };
+        }
 
-            let _ = close_button.add_event_listener_with_callback(
-                "click",
-                close_closure.as_ref().unchecked_ref(),
-            );
+        if let Some(markers_checkbox) = self.markers_checkbox() {
+            self.settings.markers = markers_checkbox.checked();
         }
 
-        self.close_closure = Some(close_closure);
-    }
+        if let Some(timer_checkbox) = self.timer_checkbox() {
+            self.settings.timer = timer_checkbox.checked();
+        }
 
-    fn set_up_help_button(&mut self) {
-        let vaflo_pointer = self as *mut Vaflo;
+        if let Some(hard_mode_checkbox) = self.hard_mode_checkbox() {
+            self.settings.hard_mode = hard_mode_checkbox.checked();
+        }
 
-        let help_closure = Closure::<dyn Fn(JsValue)>::new(
-            move |_event: JsValue| {
-                let vaflo = unsafe { &mut *vaflo_pointer };
-                vaflo.set_instructions_visibility(true);
+        if let Some(grace_days_select) = self.grace_days_select() {
+            match grace_days_select.value().parse::<u32>() {
+                Ok(grace_days) => {
+                    self.settings.streak_rules.grace_days = grace_days;
+                },
+                Err(e) => console::log_1(&e.to_string().into()),
             }
-        );
+        }
 
-        let Some(help_button) =
-            self.context.document.get_element_by_id("help-button")
-            .and_then(|c| c.dyn_into::<web_sys::EventTarget>().ok())
-        else {
-            return;
-        };
+        if let Some(same_day_checkbox) = self.same_day_checkbox() {
+            self.settings.streak_rules.same_day_only =
+                same_day_checkbox.checked();
+        }
 
-        let _ = help_button.add_event_listener_with_callback(
-            "click",
-            help_closure.as_ref().unchecked_ref(),
-        );
+        save_settings(&self.context, &self.settings);
 
-        self.help_closure = Some(help_closure);
+        // The streak rules might have changed
+        if self.statistics.is_some() && !self.is_practice {
+            self.update_statistics();
+        }
+
+        self.update_hard_mode();
+        self.update_square_states();
+        self.update_timer_display();
+        self.update_timer_interval();
     }
 
     fn create_letters(&mut self) -> Result<(), String> {
@@ -730,9 +1476,45 @@ impl Vaflo {
         }
     }
 
+    fn show_hint(&mut self) {
+        if self.game_state != GameState::Playing
+            || self.drag.is_some()
+            || !self.animated_letters.is_empty()
+            || self.hint.is_some()
+        {
+            return;
+        }
+
+        // The hint costs a swap so don’t let it make the player lose
+        if self.swaps_remaining <= 1 {
+            self.show_notice("Ne restas sufiĉe da interŝanĝoj por sugesto");
+            return;
+        }
+
+        let Some((position_a, position_b)) = hint::next_swap(&self.grid)
+        else {
+            return;
+        };
+
+        self.hint = Some((position_a, position_b));
+        self.hints_used += 1;
+        self.swaps_remaining -= 1;
+        self.save_state_dirty = true;
+
+        self.set_square_class(position_a, None);
+        self.set_square_class(position_b, None);
+        self.update_swaps_remaining();
+    }
+
     fn swap_letters(&mut self, position_a: usize, position_b: usize) {
+        // The timer starts on the first swap
+        if self.time_taken.is_none() {
+            self.resume_timer();
+        }
+
+        self.hint = None;
         self.grid.puzzle.squares.swap(position_a, position_b);
-        self.grid.update_square_states();
+        self.update_grid_square_states();
         self.update_square_states();
         self.update_square_letter(position_a);
         self.update_square_letter(position_b);
@@ -871,7 +1653,18 @@ impl Vaflo {
         if let Some(element) = self.context.document.get_element_by_id("title")
             .and_then(|c| c.dyn_into::<web_sys::HtmlElement>().ok())
         {
-            let value = format!("Vaflo #{}", self.todays_puzzle + 1);
+            let value = if self.is_practice {
+                format!(
+                    "Vaflo – praktiko{}",
+                    if self.hard_mode { "*" } else { "" },
+                )
+            } else {
+                format!(
+                    "Vaflo #{}{}",
+                    self.todays_puzzle + 1,
+                    if self.hard_mode { "*" } else { "" },
+                )
+            };
             self.set_element_text(&element, &value);
         }
     }
@@ -889,6 +1682,12 @@ impl Vaflo {
     fn set_game_state(&mut self, state: GameState) {
         self.game_state = state;
         self.update_game_state();
+
+        if state != GameState::Playing {
+            self.pause_timer();
+            self.update_timer_display();
+            self.update_timer_interval();
+        }
     }
 
     fn set_won_state(&mut self) {
@@ -961,6 +1760,21 @@ impl Vaflo {
 
         let mut class_string = class.to_string();
 
+        if let Some(palette_class) = self.settings.palette.class_name() {
+            class_string.push(' ');
+            class_string.push_str(palette_class);
+        }
+
+        if self.settings.markers {
+            class_string.push_str(" marked");
+        }
+
+        if let Some((hint_a, hint_b)) = self.hint {
+            if position == hint_a || position == hint_b {
+                class_string.push_str(" hint");
+            }
+        }
+
         if let Some(extra) = extra {
             class_string.push(' ');
             class_string.push_str(extra);
@@ -995,19 +1809,20 @@ impl Vaflo {
     }
 
     fn save_to_local_storage(&mut self) {
-        if !self.save_state_dirty {
+        if !self.save_state_dirty || self.is_practice || self.reloading {
             return;
         }
 
         if let Some(local_storage) = get_local_storage(&self.context) {
-            let mut save_states = load_save_states_from_local_storage(
+            let (mut save_states, _) = load_save_states_from_local_storage(
                 &local_storage
             );
 
-            save_states.insert(
-                self.todays_puzzle,
-                SaveState::new(self.grid.clone(), self.swaps_remaining),
-            );
+            if self.played_on.is_none() {
+                self.played_on = today().puzzle_number();
+            }
+
+            save_states.insert(self.todays_puzzle, self.current_save_state());
 
             if let Err(_) = local_storage.set_item(
                 SAVE_STATE_KEY,
@@ -1020,6 +1835,20 @@ impl Vaflo {
         }
     }
 
+    fn current_save_state(&self) -> SaveState {
+        let mut save_state = SaveState::new(
+            self.grid.clone(),
+            self.swaps_remaining,
+        );
+
+        save_state.set_hints_used(self.hints_used);
+        save_state.set_time_taken(self.elapsed_seconds());
+        save_state.set_hard_mode(self.hard_mode);
+        save_state.set_played_on(self.played_on);
+
+        save_state
+    }
+
     fn set_stats_element(&self, id: &str, value: u32) {
         if let Some(element) = self.context.document.get_element_by_id(id)
             .and_then(|c| c.dyn_into::<web_sys::HtmlElement>().ok())
@@ -1031,6 +1860,111 @@ impl Vaflo {
         }
     }
 
+    fn set_stats_time_element(&self, id: &str, seconds: Option<u32>) {
+        if let Some(element) = self.context.document.get_element_by_id(id)
+            .and_then(|c| c.dyn_into::<web_sys::HtmlElement>().ok())
+        {
+            let value = match seconds {
+                Some(seconds) => save_state::format_time(seconds),
+                None => "–".to_string(),
+            };
+            self.set_element_text(&element, &value);
+        } else {
+            console::log_1(&format!("Missing {} element", id).into());
+        }
+    }
+
+    fn set_stats_text_element(&self, id: &str, value: &str) {
+        if let Some(element) = self.context.document.get_element_by_id(id)
+            .and_then(|c| c.dyn_into::<web_sys::HtmlElement>().ok())
+        {
+            self.set_element_text(&element, value);
+        } else {
+            console::log_1(&format!("Missing {} element", id).into());
+        }
+    }
+
+    fn add_stats_row(
+        &self,
+        table_body: &web_sys::Element,
+        label: &str,
+        statistics: &save_state::PeriodStatistics,
+    ) {
+        let Ok(row) = self.context.document.create_element("tr")
+        else {
+            return;
+        };
+
+        let values = [
+            statistics.n_finished().to_string(),
+            format_win_rate(statistics.win_rate()),
+            format_average(statistics.average_stars()),
+        ];
+
+        if let Ok(header) = self.context.document.create_element("th") {
+            let _ = header.set_attribute("scope", "row");
+            header.set_text_content(Some(label));
+            let _ = row.append_child(&header);
+        }
+
+        for value in values.iter() {
+            if let Ok(cell) = self.context.document.create_element("td") {
+                cell.set_text_content(Some(value));
+                let _ = row.append_child(&cell);
+            }
+        }
+
+        let _ = table_body.append_child(&row);
+    }
+
+    fn show_period_statistics(&self, statistics: &save_state::Statistics) {
+        let overall = statistics.overall();
+
+        self.set_stats_text_element(
+            "stats-win-rate",
+            &format_win_rate(overall.win_rate()),
+        );
+        self.set_stats_text_element(
+            "stats-average-stars",
+            &format_average(overall.average_stars()),
+        );
+        self.set_stats_text_element(
+            "stats-average-swaps",
+            &format_average(overall.average_swaps_used()),
+        );
+        self.set_stats_element(
+            "stats-perfect-run",
+            statistics.best_perfect_run(),
+        );
+
+        if let Some(months) =
+            self.context.document.get_element_by_id("stats-months")
+        {
+            months.set_inner_html("");
+
+            for month in statistics.months().iter().rev().take(N_STATS_MONTHS) {
+                let label = format!(
+                    "{} {}",
+                    MONTH_NAMES[month.month as usize - 1],
+                    month.year,
+                );
+                self.add_stats_row(&months, &label, &month.statistics);
+            }
+        }
+
+        if let Some(weekdays) =
+            self.context.document.get_element_by_id("stats-weekdays")
+        {
+            weekdays.set_inner_html("");
+
+            for (name, weekday) in WEEKDAY_NAMES.iter()
+                .zip(statistics.weekdays().iter())
+            {
+                self.add_stats_row(&weekdays, name, weekday);
+            }
+        }
+    }
+
     fn show_element_as_block(&self, id: &str) {
         if let Some(element) = self.context.document.get_element_by_id(id)
             .and_then(|c| c.dyn_into::<web_sys::HtmlElement>().ok())
@@ -1039,15 +1973,84 @@ impl Vaflo {
         }
     }
 
+    fn hide_element(&self, id: &str) {
+        if let Some(element) = self.context.document.get_element_by_id(id)
+            .and_then(|c| c.dyn_into::<web_sys::HtmlElement>().ok())
+        {
+            let _ = element.style().set_property("display", "none");
+        }
+    }
+
     fn show_end_text(&mut self) {
-        let save_states = load_save_states(&self.context);
-        let statistics = save_state::Statistics::new(&save_states);
+        self.hide_element("hint-button");
+
+        self.show_definitions();
+
+        if !self.is_practice {
+            self.update_statistics();
+
+            self.show_element_as_block("share-button");
+        }
+
+        self.show_element_as_block("practice-button");
+    }
+
+    fn show_definitions(&self) {
+        let Some(list) =
+            self.context.document.get_element_by_id("definitions-list")
+        else {
+            return;
+        };
+
+        list.set_inner_html("");
+
+        if self.definitions.is_empty() {
+            self.hide_element("definitions");
+            return;
+        }
+
+        for word in definitions::grid_words(&self.grid) {
+            let Ok(item) = self.context.document.create_element("li")
+            else {
+                continue;
+            };
+
+            match self.definitions.get(&word) {
+                Some(definition) if definitions::is_link(definition) => {
+                    if let Ok(link) = self.context.document.create_element("a")
+                    {
+                        let _ = link.set_attribute("href", definition);
+                        let _ = link.set_attribute("target", "_blank");
+                        let _ = link.set_attribute("rel", "noopener");
+                        link.set_text_content(Some(&word));
+                        let _ = item.append_child(&link);
+                    }
+                },
+                Some(definition) => {
+                    item.set_text_content(
+                        Some(&format!("{} – {}", word, definition))
+                    );
+                },
+                None => item.set_text_content(Some(&word)),
+            }
+
+            let _ = list.append_child(&item);
+        }
+
+        self.show_element_as_block("definitions");
+    }
+
+    fn update_statistics(&mut self) {
+        let (save_states, _) = load_save_states(&self.context);
+        let statistics = save_state::Statistics::new(
+            &save_states,
+            self.todays_puzzle,
+            &self.settings.streak_rules,
+        );
 
         self.show_statistics(&statistics);
 
         self.statistics = Some(statistics);
-
-        self.show_element_as_block("share-button");
     }
 
     fn show_statistics(&self, statistics: &save_state::Statistics) {
@@ -1065,6 +2068,14 @@ impl Vaflo {
             "stats-fail-count",
             statistics.fail_count()
         );
+        self.set_stats_time_element(
+            "stats-best-time",
+            statistics.best_time(),
+        );
+        self.set_stats_time_element(
+            "stats-average-time",
+            statistics.average_time(),
+        );
 
         let mut n_stars_element = String::new();
 
@@ -1077,6 +2088,8 @@ impl Vaflo {
             );
         }
 
+        self.show_period_statistics(statistics);
+
         self.show_element_as_block("statistics");
     }
 
@@ -1086,10 +2099,21 @@ impl Vaflo {
             return;
         };
 
-        let share_text = statistics.share_text(
-            self.todays_puzzle,
-            &SaveState::new(self.grid.clone(), self.swaps_remaining),
-        );
+        let save_state = self.current_save_state();
+
+        let share_text = match self.share_template.as_ref() {
+            Some(template) => statistics.share_text_with_template(
+                self.todays_puzzle,
+                &save_state,
+                &self.settings,
+                template,
+            ),
+            None => statistics.share_text(
+                self.todays_puzzle,
+                &save_state,
+                &self.settings,
+            ),
+        };
 
         match self.set_clipboard_text(&share_text) {
             Ok(()) => self.show_notice("Mesaĝo kopiita al la tondujo"),
@@ -1205,30 +2229,97 @@ impl Vaflo {
     }
 }
 
+// Also returns whether any of the save states couldn’t be parsed and
+// were moved out of the way
 fn load_save_states_from_local_storage(
     local_storage: &web_sys::Storage,
-) -> HashMap<usize, SaveState> {
+) -> (HashMap<usize, SaveState>, bool) {
     match local_storage.get_item(SAVE_STATE_KEY) {
         Ok(Some(save_states)) => {
-            match save_state::load_save_states(&save_states) {
-                Ok(save_states) => save_states,
-                Err(e) => {
-                    console::log_1(&format!(
-                        "Error parsing save states: {}",
-                        e,
-                    ).into());
-                    HashMap::new()
-                },
+            let (save_states, dropped) =
+                save_state::load_save_states_lenient(&save_states);
+            let had_dropped = !dropped.is_empty();
+
+            if had_dropped {
+                quarantine_save_states(local_storage, &save_states, dropped);
             }
+
+            (save_states, had_dropped)
         },
-        Ok(None) => HashMap::new(),
+        Ok(None) => (HashMap::new(), false),
         Err(_) => {
             console::log_1(&"Error getting save states".into());
-            HashMap::new()
+            (HashMap::new(), false)
         },
     }
 }
 
+// Moves the parts of the save states that couldn’t be parsed to a
+// separate key so that they aren’t lost but also don’t get in the way
+// of the rest of the history
+fn quarantine_save_states(
+    local_storage: &web_sys::Storage,
+    save_states: &HashMap<usize, SaveState>,
+    dropped: Vec<save_state::DroppedSaveState>,
+) {
+    let mut backup = match local_storage.get_item(SAVE_STATE_BACKUP_KEY) {
+        Ok(Some(backup)) => backup,
+        _ => String::new(),
+    };
+
+    for dropped in dropped {
+        console::log_1(&format!(
+            "Dropped save state “{}”: {}",
+            dropped.fragment,
+            dropped.error,
+        ).into());
+
+        if !backup.is_empty() {
+            backup.push(',');
+        }
+
+        backup.push_str(&dropped.fragment);
+    }
+
+    if let Err(_) = local_storage.set_item(SAVE_STATE_BACKUP_KEY, &backup) {
+        console::log_1(&"Error saving the save state backup".into());
+        // Leave the original save states alone so that the bad
+        // entries aren’t lost
+        return;
+    }
+
+    let cleaned = save_state::save_states_to_string(
+        save_states.iter()
+            .map(|(&puzzle_num, save_state)| (puzzle_num, save_state.clone()))
+    );
+
+    if let Err(_) = local_storage.set_item(SAVE_STATE_KEY, &cleaned) {
+        console::log_1(&"Error saving state".into());
+    }
+}
+
+// Formats a number with one decimal place using a comma as the
+// decimal separator like in Esperanto
+fn format_average(value: Option<f32>) -> String {
+    match value {
+        Some(value) => format!("{:.1}", value).replace('.', ","),
+        None => "–".to_string(),
+    }
+}
+
+fn format_win_rate(win_rate: Option<u32>) -> String {
+    match win_rate {
+        Some(win_rate) => format!("{}%", win_rate),
+        None => "–".to_string(),
+    }
+}
+
+fn random_seed() -> u64 {
+    // Math.random only gives 52 bits so mix in the time as well
+    let random = (js_sys::Math::random() * (1u64 << 52) as f64) as u64;
+    random ^ ((js_sys::Date::now() as u64) << 12)
+}
+
 fn get_local_storage(context: &Context) -> Option<web_sys::Storage> {
     match context.window.local_storage() {
         Ok(Some(local_storage)) => Some(local_storage),
@@ -1243,11 +2334,67 @@ fn get_local_storage(context: &Context) -> Option<web_sys::Storage> {
     }
 }
 
-fn load_save_states(context: &Context) -> HashMap<usize, SaveState> {
+fn load_save_states(context: &Context) -> (HashMap<usize, SaveState>, bool) {
     if let Some(local_storage) = get_local_storage(context) {
         load_save_states_from_local_storage(&local_storage)
     } else {
-        HashMap::new()
+        (HashMap::new(), false)
+    }
+}
+
+fn load_settings(context: &Context) -> Settings {
+    let Some(local_storage) = get_local_storage(context)
+    else {
+        return Settings::default();
+    };
+
+    match local_storage.get_item(SETTINGS_KEY) {
+        Ok(Some(settings)) => {
+            match settings.parse::<Settings>() {
+                Ok(settings) => settings,
+                Err(e) => {
+                    console::log_1(&format!(
+                        "Error parsing settings: {}",
+                        e,
+                    ).into());
+                    Settings::default()
+                },
+            }
+        },
+        Ok(None) => Settings::default(),
+        Err(_) => {
+            console::log_1(&"Error getting settings".into());
+            Settings::default()
+        },
+    }
+}
+
+// Sites can change the share text by adding an element with the
+// id “share-template” containing the template
+fn load_share_template(context: &Context) -> Option<ShareTemplate> {
+    let template = context.document.get_element_by_id("share-template")?
+        .text_content()?;
+
+    match template.parse::<ShareTemplate>() {
+        Ok(template) => Some(template),
+        Err(e) => {
+            console::log_1(&format!(
+                "Error parsing share template: {}",
+                e,
+            ).into());
+            None
+        },
+    }
+}
+
+fn save_settings(context: &Context, settings: &Settings) {
+    if let Some(local_storage) = get_local_storage(context) {
+        if let Err(_) = local_storage.set_item(
+            SETTINGS_KEY,
+            &settings.to_string(),
+        ) {
+            console::log_1(&"Error saving settings".into());
+        }
     }
 }
 
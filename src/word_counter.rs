@@ -83,6 +83,14 @@ impl WordCounter {
         WordCounts { entries }
     }
 
+    // Iterates over every word as (word, count, last_use) in no
+    // particular order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, usize, usize)> {
+        self.words.values().flatten().map(|entry| {
+            (entry.word.as_str(), entry.count, entry.last_use)
+        })
+    }
+
     pub fn clear(&mut self) {
         self.words.clear();
     }
@@ -127,11 +135,24 @@ mod test {
         assert_eq!(milkings.next(), Some(("MELKI", 1, 42)));
         assert!(milkings.next().is_none());
 
+        let mut all = counter.iter().collect::<Vec<_>>();
+        all.sort_unstable();
+        assert_eq!(
+            all,
+            vec![
+                ("MELKI", 1, 42),
+                ("MELONO", 3, 4),
+                ("MELONOJ", 1, 2),
+                ("MELONOJN", 1, 4),
+            ],
+        );
+
         assert!(counter.counts("BANANOJ").next().is_none());
         assert!(counter.counts("ENGLISH").next().is_none());
 
         counter.clear();
 
         assert!(counter.counts("MELONO").next().is_none());
+        assert!(counter.iter().next().is_none());
     }
 }
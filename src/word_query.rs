@@ -0,0 +1,274 @@
+// Vaflo – A word game in Esperanto
+// Copyright (C) 2025  Neil Roberts
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+// Searches the words in a WordCounter for the editor’s word browser.
+// A query is a list of terms separated by spaces and a word has to
+// match all of them. A term can be:
+//
+// • WORD – words that start with WORD.
+// • ~WORD – words that have the same stem as WORD.
+// • #N, #N-M, #N- or #-M – words that were last used in a puzzle
+//   within the range. The puzzle numbers count from one like in the
+//   rest of the editor.
+
+use std::fmt;
+use std::str::FromStr;
+use super::word_counter::WordCounter;
+use super::stem_word;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseError {
+    InvalidRange(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::InvalidRange(term) => {
+                write!(f, "invalid puzzle range: {}", term)
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sort {
+    Alphabetical,
+    LeastRecentlyUsed,
+    MostUsed,
+}
+
+impl Sort {
+    pub fn next(self) -> Sort {
+        match self {
+            Sort::Alphabetical => Sort::LeastRecentlyUsed,
+            Sort::LeastRecentlyUsed => Sort::MostUsed,
+            Sort::MostUsed => Sort::Alphabetical,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Sort::Alphabetical => "alphabetical",
+            Sort::LeastRecentlyUsed => "least recently used",
+            Sort::MostUsed => "most used",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Entry<'a> {
+    pub word: &'a str,
+    pub count: usize,
+    pub last_use: usize,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Term {
+    Prefix(String),
+    Stem(String),
+    // Zero-based puzzle numbers, inclusive
+    Range(usize, usize),
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Query {
+    terms: Vec<Term>,
+}
+
+fn parse_puzzle_number(s: &str) -> Option<usize> {
+    s.parse::<usize>().ok()?.checked_sub(1)
+}
+
+fn parse_range(range: &str) -> Option<Term> {
+    match range.split_once('-') {
+        Some((start, end)) => {
+            let start = if start.is_empty() {
+                0
+            } else {
+                parse_puzzle_number(start)?
+            };
+            let end = if end.is_empty() {
+                usize::MAX
+            } else {
+                parse_puzzle_number(end)?
+            };
+
+            Some(Term::Range(start, end))
+        },
+        None => {
+            let puzzle_num = parse_puzzle_number(range)?;
+            Some(Term::Range(puzzle_num, puzzle_num))
+        },
+    }
+}
+
+impl FromStr for Query {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Query, ParseError> {
+        let mut terms = Vec::new();
+
+        for term in s.split_whitespace() {
+            let term = term.to_uppercase();
+
+            if let Some(range) = term.strip_prefix('#') {
+                let Some(range) = parse_range(range)
+                else {
+                    return Err(ParseError::InvalidRange(term));
+                };
+                terms.push(range);
+            } else if let Some(word) = term.strip_prefix('~') {
+                terms.push(Term::Stem(stem_word::stem(word).to_string()));
+            } else {
+                terms.push(Term::Prefix(term));
+            }
+        }
+
+        Ok(Query { terms })
+    }
+}
+
+impl Term {
+    fn matches(&self, entry: &Entry) -> bool {
+        match self {
+            Term::Prefix(prefix) => entry.word.starts_with(prefix.as_str()),
+            Term::Stem(stem) => stem_word::stem(entry.word) == stem,
+            Term::Range(start, end) => {
+                (*start..=*end).contains(&entry.last_use)
+            },
+        }
+    }
+}
+
+impl Query {
+    pub fn matches(&self, entry: &Entry) -> bool {
+        self.terms.iter().all(|term| term.matches(entry))
+    }
+}
+
+pub fn search<'a>(
+    counter: &'a WordCounter,
+    query: &Query,
+    sort: Sort,
+) -> Vec<Entry<'a>> {
+    let mut entries = counter.iter()
+        .map(|(word, count, last_use)| Entry { word, count, last_use })
+        .filter(|entry| query.matches(entry))
+        .collect::<Vec<_>>();
+
+    match sort {
+        Sort::Alphabetical => entries.sort_unstable_by_key(|e| e.word),
+        Sort::LeastRecentlyUsed => {
+            entries.sort_unstable_by_key(|e| (e.last_use, e.word))
+        },
+        Sort::MostUsed => {
+            entries.sort_unstable_by_key(|e| {
+                (std::cmp::Reverse(e.count), e.word)
+            })
+        },
+    }
+
+    entries
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn make_counter() -> WordCounter {
+        let mut counter = WordCounter::new();
+
+        counter.push("MELONO".chars(), 3);
+        counter.push("MELONOJ".chars(), 2);
+        counter.push("MELONO".chars(), 4);
+        counter.push("MELKI".chars(), 42);
+        counter.push("KATO".chars(), 10);
+
+        counter
+    }
+
+    fn words(entries: &[Entry]) -> Vec<String> {
+        entries.iter().map(|entry| entry.word.to_string()).collect()
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            "mel ~katoj #3-5 #7 #-2 #9-".parse::<Query>(),
+            Ok(Query {
+                terms: vec![
+                    Term::Prefix("MEL".to_string()),
+                    Term::Stem("KAT".to_string()),
+                    Term::Range(2, 4),
+                    Term::Range(6, 6),
+                    Term::Range(0, 1),
+                    Term::Range(8, usize::MAX),
+                ],
+            }),
+        );
+
+        assert_eq!("".parse::<Query>(), Ok(Query::default()));
+
+        assert_eq!(
+            "#0".parse::<Query>(),
+            Err(ParseError::InvalidRange("#0".to_string())),
+        );
+        assert_eq!(
+            "#x-4".parse::<Query>(),
+            Err(ParseError::InvalidRange("#X-4".to_string())),
+        );
+    }
+
+    #[test]
+    fn sorting() {
+        let counter = make_counter();
+        let query = Query::default();
+
+        assert_eq!(
+            words(&search(&counter, &query, Sort::Alphabetical)),
+            vec!["KATO", "MELKI", "MELONO", "MELONOJ"],
+        );
+        assert_eq!(
+            words(&search(&counter, &query, Sort::LeastRecentlyUsed)),
+            vec!["MELONOJ", "MELONO", "KATO", "MELKI"],
+        );
+        assert_eq!(
+            words(&search(&counter, &query, Sort::MostUsed)),
+            vec!["MELONO", "KATO", "MELKI", "MELONOJ"],
+        );
+    }
+
+    #[test]
+    fn filtering() {
+        let counter = make_counter();
+
+        let search_words = |query: &str| {
+            words(&search(
+                &counter,
+                &query.parse::<Query>().unwrap(),
+                Sort::Alphabetical,
+            ))
+        };
+
+        assert_eq!(search_words("melo"), vec!["MELONO", "MELONOJ"]);
+        assert_eq!(search_words("~melonan"), vec!["MELONO", "MELONOJ"]);
+        assert_eq!(search_words("#3-11"), vec!["KATO", "MELONO", "MELONOJ"]);
+        assert_eq!(search_words("#5 m"), vec!["MELONO"]);
+        assert_eq!(search_words("#40-"), vec!["MELKI"]);
+        assert!(search_words("hundo").is_empty());
+    }
+}
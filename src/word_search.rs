@@ -50,20 +50,11 @@ pub fn search(pattern: &str, dictionary: &Dictionary) -> Vec<String> {
 #[cfg(test)]
 mod test {
     use super::*;
-
-    // Dictionary with the words: etoso, haŭto, ninĵo, ratoj
-    static DICTIONARY_DATA: [u8; 62] = [
-        0x00, 0x01, 0x2a, 0x01, 0x13, 0x65, 0x01, 0x0a, 0x68, 0x01, 0x0a, 0x6e,
-        0x00, 0x01, 0x72, 0x00, 0x10, 0x61, 0x00, 0x10, 0x61, 0x00, 0x04, 0x69,
-        0x00, 0x04, 0x74, 0x00, 0x14, 0x6e, 0x00, 0x0b, 0x6f, 0x00, 0x05, 0x74,
-        0x00, 0x08, 0xc5, 0xad, 0x00, 0x0b, 0x6f, 0x00, 0x0b, 0x73, 0x00, 0x08,
-        0x74, 0x00, 0x05, 0xc4, 0xb5, 0x00, 0x04, 0x6a, 0x00, 0x01, 0x6f, 0x00,
-        0x00, 0x00,
-    ];
+    use super::super::dictionary::test_data;
 
     #[test]
     fn simple_search() {
-        let dictionary = Dictionary::new(Box::new(DICTIONARY_DATA.clone()));
+        let dictionary = test_data::search_words();
 
         assert_eq!(search("y.y.Y", &dictionary), &["etoso", "ninĵo"]);
         assert_eq!(search("....o", &dictionary), &["etoso", "haŭto", "ninĵo"]);
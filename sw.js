@@ -0,0 +1,168 @@
+// Vaflo – A word game in Esperanto
+// Copyright (C) 2025  Neil Roberts
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+// Service worker so that the game can be played offline. The files
+// for the page are cached when the worker is installed and the
+// puzzle files for the next few days are cached whenever the game
+// fetches a puzzle. create-dist.sh replaces the file names with the
+// names that have the checksums in them, and the cache version with
+// a checksum of the whole site so that a new version replaces the
+// old cache.
+
+const CACHE_VERSION = "dev";
+const APP_CACHE = "vaflo-app-" + CACHE_VERSION;
+const PUZZLE_CACHE = "vaflo-puzzles";
+
+const APP_FILES = [
+    "./",
+    "index.html",
+    "vaflo.js",
+    "vaflo.css",
+    "pkg/vaflo.js",
+    "pkg/vaflo_bg.wasm",
+    "empty-star.png",
+    "filled-star.png",
+    "favicon.ico",
+    "drag.svg",
+    "colors.svg",
+    "cross.svg",
+    "data/dictionary.bin",
+];
+
+// Number of days of puzzles to keep in the cache, starting from
+// yesterday in case the player’s time zone is behind the date that
+// the browser first used
+const N_CACHED_DAYS = 8;
+
+const PUZZLE_PATH_RE = /\/puzzles\/\d{4}-\d{2}-\d{2}\.bin$/;
+
+function datePath(date) {
+    const year = String(date.getFullYear()).padStart(4, "0");
+    const month = String(date.getMonth() + 1).padStart(2, "0");
+    const day = String(date.getDate()).padStart(2, "0");
+
+    return "puzzles/" + year + "-" + month + "-" + day + ".bin";
+}
+
+function puzzleWindow() {
+    const paths = [];
+    const date = new Date();
+
+    date.setDate(date.getDate() - 1);
+
+    for (let i = 0; i < N_CACHED_DAYS; i++) {
+        paths.push(new URL(datePath(date), self.registration.scope).href);
+        date.setDate(date.getDate() + 1);
+    }
+
+    return paths;
+}
+
+// Fetches the puzzles in the window that aren’t cached yet and
+// removes the ones that are too old
+async function updatePuzzleCache() {
+    const cache = await caches.open(PUZZLE_CACHE);
+    const wanted = puzzleWindow();
+
+    for (const request of await cache.keys()) {
+        if (!wanted.includes(request.url)) {
+            await cache.delete(request);
+        }
+    }
+
+    for (const url of wanted) {
+        if (await cache.match(url)) {
+            continue;
+        }
+
+        try {
+            const response = await fetch(url, { cache: "no-cache" });
+
+            // There might not be a puzzle for every day yet
+            if (response.ok) {
+                await cache.put(url, response);
+            }
+        } catch (e) {
+            // We are probably offline so try again next time
+            return;
+        }
+    }
+}
+
+async function fetchPuzzle(request) {
+    const cache = await caches.open(PUZZLE_CACHE);
+
+    try {
+        const response = await fetch(request);
+
+        if (response.ok) {
+            await cache.put(request.url, response.clone());
+        }
+
+        return response;
+    } catch (e) {
+        const cached = await cache.match(request.url);
+
+        if (cached) {
+            return cached;
+        }
+
+        // The game shows a message about being offline for this status
+        return new Response("", { status: 503, statusText: "Offline" });
+    }
+}
+
+async function fetchAppFile(request) {
+    const cached = await caches.match(request, { ignoreSearch: true });
+
+    return cached || fetch(request);
+}
+
+self.addEventListener("install", (event) => {
+    event.waitUntil(
+        caches.open(APP_CACHE)
+            .then((cache) => cache.addAll(APP_FILES))
+            .then(() => self.skipWaiting())
+    );
+});
+
+self.addEventListener("activate", (event) => {
+    event.waitUntil(
+        caches.keys()
+            .then((keys) => Promise.all(
+                keys.filter((key) => {
+                    return key.startsWith("vaflo-app-") && key != APP_CACHE;
+                }).map((key) => caches.delete(key))
+            ))
+            .then(() => updatePuzzleCache())
+            .then(() => self.clients.claim())
+    );
+});
+
+self.addEventListener("fetch", (event) => {
+    const url = new URL(event.request.url);
+
+    if (event.request.method != "GET" || url.origin != self.location.origin) {
+        return;
+    }
+
+    if (PUZZLE_PATH_RE.test(url.pathname)) {
+        event.respondWith(fetchPuzzle(event.request));
+        event.waitUntil(updatePuzzleCache());
+    } else {
+        event.respondWith(fetchAppFile(event.request));
+    }
+});
@@ -16,4 +16,10 @@
 
 import init_wasm, { init_vaflo } from "./pkg/vaflo.js";
 
+if ("serviceWorker" in navigator) {
+    navigator.serviceWorker.register("sw.js").catch((e) => {
+        console.log("Service worker registration failed: " + e);
+    });
+}
+
 init_wasm().then(() => init_vaflo());